@@ -1,14 +1,69 @@
-use std::env;
+use chrono_tz::Tz;
+use std::env::{self, VarError};
 
 pub const NOTION_API_TOKEN: &str = "NOTION_API_TOKEN";
 pub const NOTION_SOURCE_DATABASE_ID: &str = "NOTION_SOURCE_DATABASE_ID";
 pub const NOTION_FEED_DATABASE_ID: &str = "NOTION_FEED_DATABASE_ID";
+pub const NOTION_FEED_TIMEZONE: &str = "NOTION_FEED_TIMEZONE";
+pub const NOTION_FEED_STATE_FILE: &str = "NOTION_FEED_STATE_FILE";
+pub const NOTION_FEED_MAX_NEW_ITEMS: &str = "NOTION_FEED_MAX_NEW_ITEMS";
+pub const NOTION_FEED_RULES_FILE: &str = "NOTION_FEED_RULES_FILE";
+pub const NOTION_FEED_ICON_EMOJI: &str = "NOTION_FEED_ICON_EMOJI";
+pub const NOTION_FEED_PROPERTY_NAMES: &str = "NOTION_FEED_PROPERTY_NAMES";
 
 #[derive(Debug)]
 pub struct Config {
     pub notion_api_token: String,
     pub notion_source_database_id: String,
     pub notion_feed_database_id: String,
+    pub timezone: Option<Tz>,
+    pub state_file: Option<String>,
+    pub max_new_items: Option<usize>,
+    pub rules_file: Option<String>,
+    /// Icon of the feed pages whose source has no favicon
+    pub icon_emoji: Option<String>,
+    pub property_names: PropertyNames,
+}
+
+/// Names of the optional properties of feed pages. An empty name leaves the
+/// property out.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct PropertyNames {
+    pub source: String,
+    pub author: String,
+    pub tags: String,
+    pub comments: String,
+    pub enclosure: String,
+    pub media_type: String,
+    pub size: String,
+    pub duration: String,
+    pub episode: String,
+    pub views: String,
+    pub score: String,
+    pub comment_count: String,
+    pub event_date: String,
+    pub location: String,
+}
+
+impl Default for PropertyNames {
+    fn default() -> Self {
+        Self {
+            source: "Source".to_string(),
+            author: "Author".to_string(),
+            tags: "Tags".to_string(),
+            comments: "Comments".to_string(),
+            enclosure: "Enclosure".to_string(),
+            media_type: "Media type".to_string(),
+            size: "Size".to_string(),
+            duration: "Duration".to_string(),
+            episode: "Episode".to_string(),
+            views: "Views".to_string(),
+            score: "Score".to_string(),
+            comment_count: "Comment count".to_string(),
+            event_date: "Event date".to_string(),
+            location: "Location".to_string(),
+        }
+    }
 }
 
 impl Config {
@@ -22,11 +77,121 @@ impl Config {
         let notion_feed_database_id =
             get_config_value(notion_feed_database_id, NOTION_FEED_DATABASE_ID)?;
 
-        return Ok(Self {
+        Ok(Self {
             notion_api_token,
             notion_source_database_id,
             notion_feed_database_id,
-        });
+            timezone: None,
+            state_file: None,
+            max_new_items: None,
+            rules_file: None,
+            icon_emoji: None,
+            property_names: PropertyNames::default(),
+        })
+    }
+
+    pub fn with_timezone(mut self, timezone: Option<String>) -> Result<Config, String> {
+        if let Some(timezone) = get_optional_config_value(timezone, NOTION_FEED_TIMEZONE)? {
+            self.timezone = Some(
+                timezone
+                    .parse::<Tz>()
+                    .map_err(|_| format!("Invalid timezone: {:?}", timezone))?,
+            );
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_state_file(mut self, state_file: Option<String>) -> Config {
+        self.state_file = get_config_value(state_file, NOTION_FEED_STATE_FILE).ok();
+        self
+    }
+
+    pub fn with_rules_file(mut self, rules_file: Option<String>) -> Config {
+        self.rules_file = get_config_value(rules_file, NOTION_FEED_RULES_FILE).ok();
+        self
+    }
+
+    /// Sets the emoji of feed pages without favicon. Other values, like text or
+    /// several emoji, are rejected by Notion, so they are reported and ignored.
+    pub fn with_icon_emoji(mut self, icon_emoji: Option<String>) -> Config {
+        self.icon_emoji = get_config_value(icon_emoji, NOTION_FEED_ICON_EMOJI)
+            .ok()
+            .filter(|icon_emoji| {
+                let is_emoji = is_emoji(icon_emoji);
+
+                if !is_emoji {
+                    eprintln!(
+                        "Ignoring icon emoji {:?}, expected a single emoji",
+                        icon_emoji
+                    );
+                }
+
+                is_emoji
+            });
+        self
+    }
+
+    /// Renames the optional properties, e.g. `author=Writer, comments=`.
+    pub fn with_property_names(mut self, property_names: Option<String>) -> Result<Config, String> {
+        let property_names =
+            match get_optional_config_value(property_names, NOTION_FEED_PROPERTY_NAMES)? {
+                Some(property_names) => property_names,
+                None => return Ok(self),
+            };
+
+        for mapping in property_names
+            .split(',')
+            .filter(|mapping| !mapping.trim().is_empty())
+        {
+            let (key, name) = mapping.split_once('=').ok_or(format!(
+                "Invalid property name: {:?}, expected key=Name",
+                mapping
+            ))?;
+
+            let name = name.trim().to_string();
+
+            match key.trim() {
+                "source" => self.property_names.source = name,
+                "author" => self.property_names.author = name,
+                "tags" => self.property_names.tags = name,
+                "comments" => self.property_names.comments = name,
+                "enclosure" => self.property_names.enclosure = name,
+                "media_type" => self.property_names.media_type = name,
+                "size" => self.property_names.size = name,
+                "duration" => self.property_names.duration = name,
+                "episode" => self.property_names.episode = name,
+                "views" => self.property_names.views = name,
+                "score" => self.property_names.score = name,
+                "comment_count" => self.property_names.comment_count = name,
+                "event_date" => self.property_names.event_date = name,
+                "location" => self.property_names.location = name,
+                key => {
+                    return Err(format!(
+                        "Unknown property {:?}, expected source, author, tags, comments, \
+                         enclosure, media_type, size, duration, episode, views, score, \
+                         comment_count, event_date or location",
+                        key
+                    ))
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_max_new_items(mut self, max_new_items: Option<String>) -> Result<Config, String> {
+        if let Some(max_new_items) =
+            get_optional_config_value(max_new_items, NOTION_FEED_MAX_NEW_ITEMS)?
+        {
+            self.max_new_items = Some(
+                max_new_items
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid max new items: {:?}", max_new_items))?,
+            );
+        }
+
+        Ok(self)
     }
 }
 
@@ -46,7 +211,84 @@ fn get_config_value(name: Option<String>, env_name: &str) -> Result<String, Stri
         }
     }
 
-    return Err(format!("Invalid config variable: {:?}", env_var));
+    Err(format!("Invalid config variable: {:?}", env_var))
+}
+
+/// Checks that a value is a single emoji, possibly with a skin tone, a keycap
+/// or made of several emoji joined by zero width joiners, or a flag.
+fn is_emoji(value: &str) -> bool {
+    let is_pictograph = |char: char| {
+        matches!(
+            char,
+            '\u{1F000}'..='\u{1FAFF}'
+                | '\u{2190}'..='\u{21FF}'
+                | '\u{2300}'..='\u{23FF}'
+                | '\u{25A0}'..='\u{27BF}'
+                | '\u{2934}'..='\u{2935}'
+                | '\u{2B00}'..='\u{2BFF}'
+                | '\u{00A9}'
+                | '\u{00AE}'
+                | '\u{203C}'
+                | '\u{2049}'
+                | '\u{2122}'
+                | '\u{2139}'
+                | '\u{24C2}'
+                | '\u{3030}'
+                | '\u{303D}'
+                | '\u{3297}'
+                | '\u{3299}'
+        )
+    };
+    let is_regional_indicator = |char: char| matches!(char, '\u{1F1E6}'..='\u{1F1FF}');
+    // variation selector, skin tones and the tags of subdivision flags
+    let is_modifier = |char: char| {
+        matches!(
+            char,
+            '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}'
+        )
+    };
+
+    let mut chars = value.chars().peekable();
+
+    loop {
+        match chars.next() {
+            Some(char) if is_regional_indicator(char) => {
+                if !chars.next().is_some_and(is_regional_indicator) {
+                    return false;
+                }
+            }
+            Some('0'..='9' | '#' | '*') => {
+                chars.next_if_eq(&'\u{FE0F}');
+
+                if chars.next() != Some('\u{20E3}') {
+                    return false;
+                }
+            }
+            Some(char) if is_pictograph(char) => {}
+            _ => return false,
+        }
+
+        while chars.next_if(|char| is_modifier(*char)).is_some() {}
+
+        match chars.next() {
+            None => return true,
+            Some('\u{200D}') => continue,
+            Some(_) => return false,
+        }
+    }
+}
+
+/// Reads a config value which may be left out. Values which are set but
+/// invalid, like an empty argument or a non UTF-8 variable, are errors.
+fn get_optional_config_value(
+    name: Option<String>,
+    env_name: &str,
+) -> Result<Option<String>, String> {
+    match (&name, env::var(env_name)) {
+        (None, Err(VarError::NotPresent)) => Ok(None),
+        (None, Ok(env_var)) if env_var.is_empty() => Ok(None),
+        _ => get_config_value(name, env_name).map(Some),
+    }
 }
 
 #[cfg(test)]
@@ -115,4 +357,124 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn it_sets_timezone_from_args_or_env() {
+        with_vars(
+            vec![
+                (NOTION_API_TOKEN, Some("token")),
+                (NOTION_FEED_TIMEZONE, Some("Europe/Bucharest")),
+            ],
+            || {
+                let config =
+                    Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                        .and_then(|config| config.with_timezone(None))
+                        .unwrap();
+                assert_eq!(config.timezone, Some(Tz::Europe__Bucharest));
+
+                let config =
+                    Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                        .and_then(|config| config.with_timezone(Some("UTC".to_string())))
+                        .unwrap();
+                assert_eq!(config.timezone, Some(Tz::UTC));
+            },
+        );
+    }
+
+    #[test]
+    fn it_sets_max_new_items() {
+        with_vars(vec![(NOTION_API_TOKEN, Some("token"))], || {
+            let config = Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                .and_then(|config| config.with_max_new_items(Some("50".to_string())));
+            assert_eq!(config.unwrap().max_new_items, Some(50));
+
+            let config = Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                .and_then(|config| config.with_max_new_items(Some("many".to_string())));
+            assert!(config.is_err());
+        });
+    }
+
+    #[test]
+    fn it_sets_property_names() {
+        with_vars(vec![(NOTION_API_TOKEN, Some("token"))], || {
+            let config = Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                .and_then(|config| {
+                    config.with_property_names(Some("author=Writer, comments=".to_string()))
+                })
+                .unwrap();
+            assert_eq!(
+                config.property_names,
+                PropertyNames {
+                    author: "Writer".to_string(),
+                    comments: "".to_string(),
+                    ..Default::default()
+                }
+            );
+
+            let config = Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                .and_then(|config| config.with_property_names(Some("likes=Likes".to_string())));
+            assert!(config.is_err());
+        });
+    }
+
+    #[test]
+    fn it_fails_when_optional_values_are_set_but_empty() {
+        with_vars(
+            vec![
+                (NOTION_API_TOKEN, Some("token")),
+                (NOTION_FEED_TIMEZONE, None),
+                (NOTION_FEED_MAX_NEW_ITEMS, Some("")),
+            ],
+            || {
+                let config =
+                    Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                        .and_then(|config| config.with_timezone(None))
+                        .and_then(|config| config.with_max_new_items(None))
+                        .and_then(|config| config.with_property_names(None))
+                        .unwrap();
+                assert_eq!(config.timezone, None);
+                assert_eq!(config.max_new_items, None);
+
+                let config =
+                    Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                        .and_then(|config| config.with_timezone(Some("".to_string())));
+                assert!(config.is_err());
+
+                let config =
+                    Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                        .and_then(|config| config.with_property_names(Some("".to_string())));
+                assert!(config.is_err());
+            },
+        );
+    }
+
+    #[test]
+    fn it_keeps_single_emoji_as_icon() {
+        with_vars(vec![(NOTION_API_TOKEN, Some("token"))], || {
+            let icon_emoji = |icon_emoji: &str| {
+                Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                    .map(|config| config.with_icon_emoji(Some(icon_emoji.to_string())))
+                    .unwrap()
+                    .icon_emoji
+            };
+
+            for emoji in ["📰", "☕", "❤️", "👍🏽", "👩‍💻", "🇷🇴", "1️⃣"]
+            {
+                assert_eq!(icon_emoji(emoji), Some(emoji.to_string()));
+            }
+
+            for value in ["news", "📰📰", "📰 ", "1", "🇷"] {
+                assert_eq!(icon_emoji(value), None);
+            }
+        });
+    }
+
+    #[test]
+    fn it_fails_when_timezone_is_invalid() {
+        with_vars(vec![(NOTION_API_TOKEN, Some("token"))], || {
+            let config = Config::new(Some("source db".to_string()), Some("feed db".to_string()))
+                .and_then(|config| config.with_timezone(Some("Mars/Olympus".to_string())));
+            assert!(config.is_err());
+        });
+    }
 }
@@ -0,0 +1,166 @@
+use atom_syndication::{extension::ExtensionMap as AtomExtensionMap, Feed, Link};
+use rss::{
+    extension::{Extension, ExtensionMap},
+    Category, Channel, Item,
+};
+use std::{collections::BTreeMap, io::BufRead};
+
+use super::html::{from_text, is_absolute};
+
+/// Reads an Atom feed, like the YouTube channel feeds, as an RSS channel so
+/// that its entries go through the same pipeline as RSS items.
+pub fn read_channel<R: BufRead>(reader: R) -> Result<Channel, atom_syndication::Error> {
+    let feed = Feed::read_from(reader)?;
+
+    let items = feed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let description = entry
+                .summary
+                .map(|summary| summary.value)
+                .or_else(|| media_description(&entry.extensions));
+
+            Item {
+                title: Some(entry.title.value),
+                link: alternate_link(&entry.links).or(Some(entry.id).filter(|id| is_absolute(id))),
+                description,
+                author: Some(
+                    entry
+                        .authors
+                        .iter()
+                        .map(|author| author.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", "),
+                )
+                .filter(|authors| !authors.is_empty()),
+                categories: entry
+                    .categories
+                    .into_iter()
+                    .map(|category| Category {
+                        name: category.label.unwrap_or(category.term),
+                        domain: None,
+                    })
+                    .collect(),
+                comments: entry
+                    .links
+                    .iter()
+                    .find(|link| link.rel == "replies")
+                    .map(|link| link.href.to_string()),
+                pub_date: Some(entry.published.unwrap_or(entry.updated).to_rfc2822()),
+                content: entry.content.and_then(|content| content.value),
+                extensions: extensions(entry.extensions),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(Channel {
+        title: feed.title.value,
+        link: alternate_link(&feed.links).unwrap_or_default(),
+        description: feed
+            .subtitle
+            .map(|subtitle| subtitle.value)
+            .unwrap_or_default(),
+        items,
+        ..Default::default()
+    })
+}
+
+fn alternate_link(links: &[Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| link.rel == "alternate")
+        .map(|link| link.href.to_string())
+}
+
+/// Descriptions of `media:group` are plain text, converted to paragraphs.
+fn media_description(extensions: &AtomExtensionMap) -> Option<String> {
+    let description = extensions
+        .get("media")?
+        .values()
+        .flatten()
+        .flat_map(|extension| {
+            std::iter::once(extension).chain(extension.children.values().flatten())
+        })
+        .find(|extension| extension.name.ends_with("description"))?
+        .value
+        .as_deref()?;
+
+    Some(from_text(description)).filter(|paragraphs| !paragraphs.is_empty())
+}
+
+/// Atom and RSS extensions have the same shape, but different types.
+fn extensions(map: AtomExtensionMap) -> ExtensionMap {
+    map.into_iter()
+        .map(|(prefix, elements)| {
+            let elements = elements
+                .into_iter()
+                .map(|(name, values)| (name, values.into_iter().map(extension).collect()))
+                .collect::<BTreeMap<String, Vec<Extension>>>();
+
+            (prefix, elements)
+        })
+        .collect()
+}
+
+fn extension(extension: atom_syndication::extension::Extension) -> Extension {
+    Extension {
+        name: extension.name,
+        value: extension.value,
+        attrs: extension.attrs,
+        children: extension
+            .children
+            .into_iter()
+            .map(|(name, values)| (name, values.into_iter().map(self::extension).collect()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_atom_entries_as_items() {
+        let channel = read_channel(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+                <title>Blog</title>
+                <id>urn:uuid:blog</id>
+                <updated>2022-09-05T10:00:00Z</updated>
+                <link rel="self" href="https://bereghici.dev/atom.xml"/>
+                <link rel="alternate" href="https://bereghici.dev"/>
+                <entry>
+                    <title>Release</title>
+                    <id>https://bereghici.dev/release</id>
+                    <updated>2022-09-06T10:00:00Z</updated>
+                    <published>2022-09-05T10:00:00+02:00</published>
+                    <author><name>Alexandru</name></author>
+                    <category term="rust" label="Rust"/>
+                    <link rel="replies" href="https://bereghici.dev/release#comments"/>
+                    <content type="html">&lt;p&gt;Hello&lt;/p&gt;</content>
+                </entry>
+            </feed>"#
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(channel.title, "Blog");
+        assert_eq!(channel.link, "https://bereghici.dev");
+
+        let item = &channel.items[0];
+        assert_eq!(item.link.as_deref(), Some("https://bereghici.dev/release"));
+        assert_eq!(item.author.as_deref(), Some("Alexandru"));
+        assert_eq!(item.categories[0].name, "Rust");
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://bereghici.dev/release#comments")
+        );
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Mon, 5 Sep 2022 10:00:00 +0200")
+        );
+        assert_eq!(item.content.as_deref(), Some("<p>Hello</p>"));
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use ical::{parser::ical::component::IcalEvent, property::Property, IcalParser};
+use rss::{Category, Channel, Guid, Item};
+use std::{error::Error, fmt, str::FromStr};
+
+use super::{html::from_text, metadata};
+
+/// Reads the events of an iCalendar feed. An event links to its `URL`, or to
+/// the feed with its `UID` as fragment, and is dated by its last modification,
+/// so that it's read again when it changes.
+pub fn read_channel(content: &[u8], link: &str) -> Result<Channel, Box<dyn Error>> {
+    let mut channel = Channel {
+        link: link.to_string(),
+        ..Default::default()
+    };
+
+    for calendar in IcalParser::new(content) {
+        let calendar = calendar?;
+
+        if let Some(name) = value(&calendar.properties, "X-WR-CALNAME") {
+            channel.title = unescape(name);
+        }
+
+        channel
+            .items
+            .extend(calendar.events.iter().filter_map(|event| item(event, link)));
+    }
+
+    Ok(channel)
+}
+
+/// Start or end of an event.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EventTime {
+    /// Day of an all-day event
+    Day(NaiveDate),
+    /// Floating time, the same in every time zone
+    Local(NaiveDateTime),
+    Time(DateTime<FixedOffset>),
+}
+
+impl EventTime {
+    /// Returns the time in a time zone: floating times are read in the time
+    /// zone, or in UTC without, and days start at midnight UTC.
+    pub fn in_time_zone(&self, time_zone: Option<Tz>) -> Option<DateTime<FixedOffset>> {
+        match (self, time_zone) {
+            (Self::Day(date), _) => Some(date.and_time(NaiveTime::MIN).and_utc().fixed_offset()),
+            (Self::Local(date), Some(time_zone)) => time_zone
+                .from_local_datetime(date)
+                .earliest()
+                .map(|date| date.fixed_offset()),
+            (Self::Local(date), None) => Some(date.and_utc().fixed_offset()),
+            (Self::Time(date), _) => Some(*date),
+        }
+    }
+}
+
+/// Days are written as `2024-09-12`, floating times as `2024-09-12T09:00:00`
+/// and the other times in RFC 3339.
+impl fmt::Display for EventTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Day(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Self::Local(date) => write!(f, "{}", date.format("%Y-%m-%dT%H:%M:%S")),
+            Self::Time(date) => write!(f, "{}", date.to_rfc3339()),
+        }
+    }
+}
+
+impl FromStr for EventTime {
+    type Err = chrono::ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        DateTime::parse_from_rfc3339(input)
+            .map(Self::Time)
+            .or_else(|_| NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S").map(Self::Local))
+            .or_else(|_| NaiveDate::parse_from_str(input, "%Y-%m-%d").map(Self::Day))
+    }
+}
+
+/// Converts an event to an item, with its date range and location in the item
+/// metadata. Events without start are left out.
+fn item(event: &IcalEvent, link: &str) -> Option<Item> {
+    let properties = &event.properties;
+    let uid = value(properties, "UID");
+    let start = property(properties, "DTSTART").and_then(date)?;
+
+    let end = property(properties, "DTEND")
+        .and_then(date)
+        .map(|end| match end {
+            // the end date of all-day events is exclusive
+            EventTime::Day(end) => EventTime::Day(end - Duration::days(1)),
+            end => end,
+        })
+        .filter(|end| end.in_time_zone(None) > start.in_time_zone(None));
+
+    let link = value(properties, "URL")
+        .map(str::to_string)
+        .or_else(|| uid.map(|uid| format!("{}#{}", link, uid)))?;
+
+    // `DTSTAMP` is usually the time the feed was generated
+    let pub_date = ["LAST-MODIFIED", "CREATED"]
+        .into_iter()
+        .find_map(|name| property(properties, name).and_then(date))
+        .and_then(|date| date.in_time_zone(None))
+        .map(|date| date.to_rfc2822());
+
+    let mut item = Item {
+        title: Some(
+            value(properties, "SUMMARY")
+                .map(unescape)
+                .unwrap_or_else(|| "(no title)".to_string()),
+        ),
+        link: Some(link),
+        description: value(properties, "DESCRIPTION").map(|text| from_text(&unescape(text))),
+        categories: value(properties, "CATEGORIES")
+            .map(|categories| {
+                split(categories)
+                    .into_iter()
+                    .map(|name| Category { name, domain: None })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        pub_date,
+        guid: uid.map(|uid| Guid {
+            value: uid.to_string(),
+            permalink: false,
+        }),
+        ..Default::default()
+    };
+
+    metadata::set_event_date(&mut item, start, end);
+
+    if let Some(location) = value(properties, "LOCATION")
+        .map(unescape)
+        .filter(|location| !location.trim().is_empty())
+    {
+        metadata::set_location(&mut item, &location);
+    }
+
+    Some(item)
+}
+
+fn property<'a>(properties: &'a [Property], name: &str) -> Option<&'a Property> {
+    properties.iter().find(|property| property.name == name)
+}
+
+fn value<'a>(properties: &'a [Property], name: &str) -> Option<&'a str> {
+    property(properties, name)?.value.as_deref()
+}
+
+fn param<'a>(property: &'a Property, name: &str) -> Option<&'a str> {
+    property
+        .params
+        .as_ref()?
+        .iter()
+        .find(|(param, _)| param == name)?
+        .1
+        .first()
+        .map(String::as_str)
+}
+
+/// Parses a date-time value in UTC (`20240911T090000Z`), in the time zone of its
+/// `TZID` parameter, or floating. Dates (`20240911`) are all-day.
+fn date(property: &Property) -> Option<EventTime> {
+    let value = property.value.as_deref()?.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some(EventTime::Day(date));
+    }
+
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+
+    let date = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let time_zone = param(property, "TZID")
+        .filter(|_| !utc)
+        .and_then(|time_zone| time_zone.trim_matches('"').parse::<Tz>().ok());
+
+    match (utc, time_zone) {
+        (true, _) => Some(EventTime::Time(date.and_utc().fixed_offset())),
+        (false, Some(time_zone)) => Some(EventTime::Time(
+            time_zone
+                .from_local_datetime(&date)
+                .earliest()?
+                .fixed_offset(),
+        )),
+        (false, None) => Some(EventTime::Local(date)),
+    }
+}
+
+/// Unescapes a text value: `\n`, `\,`, `\;` and `\\`.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(char) => unescaped.push(char),
+                None => unescaped.push('\\'),
+            },
+            char => unescaped.push(char),
+        }
+    }
+
+    unescaped
+}
+
+/// Splits a list value on its unescaped commas.
+fn split(text: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut escaped = false;
+
+    for char in text.chars() {
+        match char {
+            ',' if !escaped => values.push(String::new()),
+            char => values.last_mut().unwrap().push(char),
+        }
+
+        escaped = char == '\\' && !escaped;
+    }
+
+    values
+        .iter()
+        .map(|value| unescape(value.trim()))
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINK: &str = "https://rustconf.dev/schedule.ics";
+
+    #[test]
+    fn it_reads_events() {
+        let channel = read_channel(include_bytes!("../../fixtures/conference.ics"), LINK).unwrap();
+
+        assert_eq!(channel.title, "RustConf 2024");
+        assert_eq!(channel.items.len(), 4);
+
+        let item = &channel.items[0];
+        assert_eq!(item.title.as_deref(), Some("Opening keynote"));
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://rustconf.dev/talks/keynote")
+        );
+        assert_eq!(
+            item.description.as_deref(),
+            Some("<p>Welcome to RustConf!</p><p>The state of the Rust project. Doors open at 8:30.</p>")
+        );
+        assert_eq!(item.categories.len(), 2);
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Mon, 2 Sep 2024 10:15:00 +0000")
+        );
+        assert_eq!(
+            metadata::location(item).as_deref(),
+            Some("Hall A, Palais des congrès")
+        );
+
+        let (start, end) = metadata::event_date(item).unwrap();
+        assert_eq!(start.to_string(), "2024-09-11T09:00:00-04:00");
+        assert_eq!(end.unwrap().to_string(), "2024-09-11T10:00:00-04:00");
+    }
+
+    #[test]
+    fn it_reads_all_day_events_and_events_without_url() {
+        let channel = read_channel(include_bytes!("../../fixtures/conference.ics"), LINK).unwrap();
+
+        let item = &channel.items[1];
+        let (start, end) = metadata::event_date(item).unwrap();
+        assert_eq!(start.to_string(), "2024-09-12");
+        assert_eq!(end.unwrap().to_string(), "2024-09-13");
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://rustconf.dev/schedule.ics#unconf-2024@rustconf.dev")
+        );
+
+        let item = &channel.items[2];
+        assert_eq!(item.pub_date, None);
+        assert_eq!(metadata::event_date(item).unwrap().1, None);
+        assert_eq!(metadata::location(item), None);
+    }
+
+    #[test]
+    fn it_reads_floating_times_in_the_time_zone() {
+        let channel = read_channel(include_bytes!("../../fixtures/conference.ics"), LINK).unwrap();
+
+        let (start, end) = metadata::event_date(&channel.items[3]).unwrap();
+        assert_eq!(start.to_string(), "2024-09-12T14:00:00");
+        assert_eq!(end.unwrap().to_string(), "2024-09-12T17:00:00");
+        assert_eq!(
+            start
+                .in_time_zone(Some(Tz::America__Montreal))
+                .unwrap()
+                .to_rfc3339(),
+            "2024-09-12T14:00:00-04:00"
+        );
+        assert_eq!(
+            start.in_time_zone(None).unwrap().to_rfc3339(),
+            "2024-09-12T14:00:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use reqwest::Url;
+use rss::{extension::Extension, Item};
+use scraper::{Html, Selector};
+
+use super::html::is_absolute;
+
+/// Finds the image of an item, used as the cover of its page: a
+/// `media:thumbnail`, an image `media:content` or enclosure, the episode image
+/// of podcasts, or the first image of its content.
+pub fn item_image(item: &Item) -> Option<String> {
+    let media = item
+        .extensions
+        .get("media")
+        .into_iter()
+        .flat_map(|media| media.values().flatten())
+        .flat_map(|extension| {
+            // `media:group` holds several versions of the same media
+            std::iter::once(extension).chain(extension.children.values().flatten())
+        })
+        .collect::<Vec<&Extension>>();
+
+    let thumbnail = media
+        .iter()
+        .find(|extension| extension.name.ends_with("thumbnail"))
+        .and_then(|extension| extension.attrs.get("url"));
+
+    let content = media
+        .iter()
+        .filter(|extension| extension.name.ends_with("content"))
+        .find(|extension| {
+            extension
+                .attrs
+                .get("medium")
+                .is_some_and(|medium| medium == "image")
+                || extension
+                    .attrs
+                    .get("type")
+                    .is_some_and(|mime_type| mime_type.starts_with("image/"))
+        })
+        .and_then(|extension| extension.attrs.get("url"));
+
+    let enclosure = item
+        .enclosure
+        .as_ref()
+        .filter(|enclosure| enclosure.mime_type.starts_with("image/"))
+        .map(|enclosure| &enclosure.url);
+
+    let episode_image = item
+        .itunes_ext
+        .as_ref()
+        .and_then(|itunes| itunes.image.as_ref());
+
+    // a relative URL can't be resolved, the next candidate is used instead
+    [thumbnail, content, enclosure, episode_image]
+        .into_iter()
+        .flatten()
+        .find(|url| is_absolute(url))
+        .cloned()
+        .or_else(|| {
+            item.content
+                .as_deref()
+                .or(item.description.as_deref())
+                .and_then(first_image)
+        })
+}
+
+/// Finds the image of a web page from its Open Graph or Twitter card metadata.
+pub fn page_image(html: &str, base: &Url) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(
+        r#"meta[property="og:image"], meta[name="og:image"], meta[name="twitter:image"]"#,
+    )
+    .unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|meta| meta.value().attr("content"))
+        .find_map(|url| base.join(url.trim()).ok())
+        .map(|url| url.to_string())
+        .filter(|url| is_absolute(url))
+}
+
+/// Finds the icon declared by a web page.
+pub fn page_icon(html: &str, base: &Url) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(r#"link[rel~="icon"], link[rel="apple-touch-icon"]"#).unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|link| link.value().attr("href"))
+        .find_map(|href| base.join(href.trim()).ok())
+        .map(|url| url.to_string())
+        .filter(|url| is_absolute(url))
+}
+
+fn first_image(html: &str) -> Option<String> {
+    let fragment = Html::parse_fragment(html);
+    let selector = Selector::parse("img[src]").unwrap();
+
+    fragment
+        .select(&selector)
+        .filter_map(|img| img.value().attr("src"))
+        .find(|src| is_absolute(src))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rss::Channel;
+
+    fn items(xml_items: &str) -> Vec<Item> {
+        let xml = format!(
+            r#"<rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel>
+                <title>Blog</title><link>https://bereghici.dev</link><description>Blog</description>
+                {}
+            </channel></rss>"#,
+            xml_items
+        );
+
+        Channel::read_from(xml.as_bytes()).unwrap().items
+    }
+
+    #[test]
+    fn it_finds_item_images() {
+        let items = items(
+            r#"
+            <item>
+                <title>Thumbnail</title>
+                <media:content url="https://bereghici.dev/video.mp4" medium="video"/>
+                <media:thumbnail url="https://bereghici.dev/thumbnail.jpg"/>
+            </item>
+            <item>
+                <title>Group</title>
+                <media:group>
+                    <media:content url="https://bereghici.dev/large.png" type="image/png"/>
+                </media:group>
+            </item>
+            <item>
+                <title>Enclosure</title>
+                <enclosure url="https://bereghici.dev/cover.webp" length="1" type="image/webp"/>
+            </item>
+            <item>
+                <title>Content</title>
+                <enclosure url="https://bereghici.dev/episode.mp3" length="1" type="audio/mpeg"/>
+                <description><![CDATA[<img src="/relative.png"><img src="https://bereghici.dev/first.gif">]]></description>
+            </item>
+            <item>
+                <title>Episode</title>
+                <media:thumbnail url="/thumbnail.jpg"/>
+                <itunes:image href="https://bereghici.dev/episode.jpg"/>
+            </item>
+            <item><title>Nothing</title><description>Text only</description></item>
+            "#,
+        );
+
+        assert_eq!(
+            items
+                .iter()
+                .map(item_image)
+                .collect::<Vec<Option<String>>>(),
+            vec![
+                Some("https://bereghici.dev/thumbnail.jpg".to_string()),
+                Some("https://bereghici.dev/large.png".to_string()),
+                Some("https://bereghici.dev/cover.webp".to_string()),
+                Some("https://bereghici.dev/first.gif".to_string()),
+                Some("https://bereghici.dev/episode.jpg".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn it_finds_page_images() {
+        let base = Url::parse("https://bereghici.dev/blog/post").unwrap();
+
+        assert_eq!(
+            page_image(
+                r#"<html><head><meta property="og:image" content="/images/og.png"></head></html>"#,
+                &base
+            ),
+            Some("https://bereghici.dev/images/og.png".to_string())
+        );
+        assert_eq!(page_image("<html><head></head></html>", &base), None);
+    }
+
+    #[test]
+    fn it_finds_page_icons() {
+        let base = Url::parse("https://bereghici.dev/blog").unwrap();
+
+        assert_eq!(
+            page_icon(
+                r#"<head><link rel="stylesheet" href="/style.css"><link rel="shortcut icon" href="favicon.png"></head>"#,
+                &base
+            ),
+            Some("https://bereghici.dev/favicon.png".to_string())
+        );
+        assert_eq!(page_icon("<head></head>", &base), None);
+    }
+}
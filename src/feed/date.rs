@@ -0,0 +1,579 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// Month names (lowercase, without trailing dots) found in real feeds, mapped
+/// to the English abbreviation understood by chrono.
+const MONTHS: [(&str, &[&str]); 12] = [
+    (
+        "Jan",
+        &[
+            "jan", "january", "janv", "janvier", "januar", "jänner", "jän", "ene", "enero", "gen",
+            "gennaio", "janeiro", "januari", "ian", "ianuarie",
+        ],
+    ),
+    (
+        "Feb",
+        &[
+            "feb",
+            "february",
+            "févr",
+            "fevr",
+            "février",
+            "fevrier",
+            "februar",
+            "febrero",
+            "febbraio",
+            "fev",
+            "fevereiro",
+            "februari",
+            "februarie",
+        ],
+    ),
+    (
+        "Mar",
+        &[
+            "mar", "march", "mars", "märz", "mär", "maerz", "mrz", "marzo", "março", "marco",
+            "maart", "mrt", "martie",
+        ],
+    ),
+    (
+        "Apr",
+        &[
+            "apr", "april", "avr", "avril", "abr", "abril", "aprile", "aprilie",
+        ],
+    ),
+    (
+        "May",
+        &["may", "mai", "mayo", "mag", "maggio", "maio", "mei"],
+    ),
+    (
+        "Jun",
+        &[
+            "jun", "june", "juin", "juni", "junio", "giu", "giugno", "junho", "iun", "iunie",
+        ],
+    ),
+    (
+        "Jul",
+        &[
+            "jul", "july", "juil", "juillet", "juli", "julio", "lug", "luglio", "julho", "iul",
+            "iulie",
+        ],
+    ),
+    (
+        "Aug",
+        &["aug", "august", "août", "aout", "ago", "agosto", "augustus"],
+    ),
+    (
+        "Sep",
+        &[
+            "sep",
+            "sept",
+            "september",
+            "septembre",
+            "septiembre",
+            "setiembre",
+            "set",
+            "settembre",
+            "setembro",
+            "septembrie",
+        ],
+    ),
+    (
+        "Oct",
+        &[
+            "oct",
+            "october",
+            "octobre",
+            "okt",
+            "oktober",
+            "octubre",
+            "ott",
+            "ottobre",
+            "out",
+            "outubro",
+            "octombrie",
+        ],
+    ),
+    (
+        "Nov",
+        &[
+            "nov",
+            "november",
+            "novembre",
+            "noviembre",
+            "novembro",
+            "noiembrie",
+        ],
+    ),
+    (
+        "Dec",
+        &[
+            "dec",
+            "december",
+            "déc",
+            "décembre",
+            "decembre",
+            "dez",
+            "dezember",
+            "dic",
+            "diciembre",
+            "dicembre",
+            "dezembro",
+            "decembrie",
+        ],
+    ),
+];
+
+const WEEKDAYS: &[&str] = &[
+    // English
+    "mon",
+    "monday",
+    "tue",
+    "tues",
+    "tuesday",
+    "wed",
+    "weds",
+    "wednesday",
+    "thu",
+    "thur",
+    "thurs",
+    "thursday",
+    "fri",
+    "friday",
+    "sat",
+    "saturday",
+    "sun",
+    "sunday",
+    // French, Spanish, Italian, Romanian
+    "lun",
+    "lundi",
+    "lunes",
+    "lunedì",
+    "luni",
+    "mar",
+    "mardi",
+    "martes",
+    "martedì",
+    "marți",
+    "marti",
+    "mer",
+    "mercredi",
+    "mercoledì",
+    "mié",
+    "mie",
+    "miércoles",
+    "miercoles",
+    "miercuri",
+    "jeu",
+    "jeudi",
+    "jue",
+    "jueves",
+    "gio",
+    "giovedì",
+    "joi",
+    "ven",
+    "vendredi",
+    "vie",
+    "viernes",
+    "venerdì",
+    "vin",
+    "vineri",
+    "sam",
+    "samedi",
+    "sáb",
+    "sab",
+    "sábado",
+    "sabado",
+    "sabato",
+    "sâm",
+    "sâmbătă",
+    "sambata",
+    "dim",
+    "dimanche",
+    "dom",
+    "domingo",
+    "domenica",
+    "dum",
+    "duminică",
+    "duminica",
+    // German, Dutch, Portuguese
+    "mo",
+    "montag",
+    "di",
+    "dienstag",
+    "mi",
+    "mittwoch",
+    "do",
+    "donnerstag",
+    "fr",
+    "freitag",
+    "sa",
+    "samstag",
+    "sonnabend",
+    "so",
+    "sonntag",
+    "ma",
+    "maandag",
+    "dinsdag",
+    "wo",
+    "woensdag",
+    "donderdag",
+    "vr",
+    "vrijdag",
+    "za",
+    "zaterdag",
+    "zo",
+    "zondag",
+    "seg",
+    "segunda",
+    "ter",
+    "terça",
+    "terca",
+    "qua",
+    "quarta",
+    "qui",
+    "quinta",
+    "sex",
+    "sexta",
+];
+
+/// Time zone abbreviations commonly used in RSS dates, with their offset in minutes.
+const ZONES: &[(&str, i32)] = &[
+    ("gmt", 0),
+    ("ut", 0),
+    ("utc", 0),
+    ("z", 0),
+    ("wet", 0),
+    ("west", 60),
+    ("bst", 60),
+    ("cet", 60),
+    ("cest", 120),
+    ("met", 60),
+    ("mest", 120),
+    ("eet", 120),
+    ("eest", 180),
+    ("msk", 180),
+    ("ist", 330),
+    ("sgt", 480),
+    ("hkt", 480),
+    ("awst", 480),
+    ("jst", 540),
+    ("kst", 540),
+    ("acst", 570),
+    ("aest", 600),
+    ("aedt", 660),
+    ("nzst", 720),
+    ("nzdt", 780),
+    ("ast", -240),
+    ("adt", -180),
+    ("est", -300),
+    ("edt", -240),
+    ("cst", -360),
+    ("cdt", -300),
+    ("mst", -420),
+    ("mdt", -360),
+    ("pst", -480),
+    ("pdt", -420),
+    ("akst", -540),
+    ("akdt", -480),
+    ("hst", -600),
+];
+
+const DATE_FORMATS: &[&str] = &[
+    "%d %b %Y", "%b %d %Y", "%d-%b-%Y", "%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y",
+];
+
+const TIME_FORMATS: &[&str] = &[
+    "%H:%M:%S%.f",
+    "%H:%M:%S",
+    "%H:%M",
+    "%I:%M:%S %p",
+    "%I:%M %p",
+];
+
+static WEEKDAY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([\p{L}]+)(?:-[\p{L}]+)?\.?(,?)\s+").unwrap());
+static WORD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[\p{L}]+\.?").unwrap());
+static DAY_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\d{1,2})\.\s").unwrap());
+static MERIDIEM_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b([ap])\.m\.").unwrap());
+static TIME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\d{1,2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:\s*[ap]m\b)?").unwrap()
+});
+static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(.*\)").unwrap());
+static OFFSET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^([a-z]{1,5})?\s*(?:([+-])(\d{1,2})(?::?(\d{2}))?)?$").unwrap()
+});
+static YEAR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\d{1,2} [A-Z][a-z]{2}|[A-Z][a-z]{2} \d{1,2}) (\d{2})\b").unwrap()
+});
+
+/// Parses publication dates as they appear in the wild: RFC 2822 and RFC 3339,
+/// but also time zone abbreviations, `GMT+2` style offsets, two-digit years,
+/// missing weekdays, localized month names and dates without time or time zone.
+/// Dates without a time zone are considered UTC, dates with an unknown time zone
+/// abbreviation are rejected.
+pub fn parse_date(input: &str) -> Option<DateTime<FixedOffset>> {
+    let input = input.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc2822(input) {
+        return Some(date);
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Some(date);
+    }
+
+    let input = normalize(input);
+    let (input, offset) = split_offset(&input)?;
+
+    parse_naive(&input)?.and_local_timezone(offset).single()
+}
+
+/// Removes weekdays, commas and filler words and translates month names,
+/// so that the date can be matched by a small set of formats.
+fn normalize(input: &str) -> String {
+    let mut input = input.to_string();
+
+    if let Some(captures) = WEEKDAY_RE.captures(&input) {
+        let word = captures[1].to_lowercase();
+        let has_comma = !captures[2].is_empty();
+
+        if WEEKDAYS.contains(&word.as_str()) && (has_comma || month(&word).is_none()) {
+            input = input[captures[0].len()..].to_string();
+        }
+    }
+
+    let input = MERIDIEM_RE.replace_all(&input, "${1}m");
+    let input = DAY_RE.replace_all(&input, "$1 ");
+    let input = WORD_RE.replace_all(&input, |captures: &Captures| {
+        let word = captures[0].trim_end_matches('.').to_lowercase();
+
+        match word.as_str() {
+            "de" | "del" => "".to_string(),
+            word => match month(word) {
+                Some(month) => month.to_string(),
+                None => captures[0].to_string(),
+            },
+        }
+    });
+
+    input
+        .replace(',', " ")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn month(word: &str) -> Option<&'static str> {
+    MONTHS
+        .iter()
+        .find(|(_, names)| names.contains(&word))
+        .map(|(month, _)| *month)
+}
+
+/// Splits the time zone that follows the time of day from the rest of the date.
+fn split_offset(input: &str) -> Option<(String, FixedOffset)> {
+    let time = match TIME_RE.find(input) {
+        Some(time) => time,
+        None => return Some((input.to_string(), FixedOffset::east_opt(0)?)),
+    };
+
+    let offset = parse_offset(&input[time.end()..])?;
+
+    Some((input[..time.end()].to_string(), offset))
+}
+
+fn parse_offset(input: &str) -> Option<FixedOffset> {
+    let input = COMMENT_RE.replace_all(input, "");
+    let captures = OFFSET_RE.captures(input.trim())?;
+
+    let zone = match captures.get(1) {
+        // unknown abbreviations are rejected rather than guessed
+        Some(zone) => ZONES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(zone.as_str()))
+            .map(|(_, minutes)| *minutes)?,
+        None => 0,
+    };
+
+    let offset = match (captures.get(2), captures.get(3)) {
+        (Some(sign), Some(hours)) => {
+            let hours = hours.as_str().parse::<i32>().ok()?;
+            let minutes = match captures.get(4) {
+                Some(minutes) => minutes.as_str().parse::<i32>().ok()?,
+                None => 0,
+            };
+            let minutes = hours * 60 + minutes;
+
+            match sign.as_str() {
+                "-" => -minutes,
+                _ => minutes,
+            }
+        }
+        _ => 0,
+    };
+
+    FixedOffset::east_opt((zone + offset) * 60)
+}
+
+fn parse_naive(input: &str) -> Option<NaiveDateTime> {
+    let input = expand_year(input);
+
+    for date_format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(&input, date_format) {
+            return date.and_hms_opt(0, 0, 0);
+        }
+
+        for separator in [" ", "T"] {
+            for time_format in TIME_FORMATS {
+                let format = format!("{}{}{}", date_format, separator, time_format);
+
+                if let Ok(date) = NaiveDateTime::parse_from_str(&input, &format) {
+                    return Some(date);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Two-digit years are expanded (`22` -> `2022`, `98` -> `1998`), since chrono
+/// would otherwise read them as years of the first century.
+fn expand_year(input: &str) -> String {
+    YEAR_RE
+        .replace(input, |captures: &Captures| {
+            let year = captures[2].parse::<u32>().unwrap_or(0);
+            let century = if year < 70 { 2000 } else { 1900 };
+
+            format!("{} {}", &captures[1], century + year)
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_date;
+
+    #[test]
+    fn it_parses_dates_from_real_feeds() {
+        let corpus = [
+            // RFC 2822 and RFC 3339
+            (
+                "Mon, 05 Sep 2022 10:00:00 +0000",
+                "2022-09-05T10:00:00+00:00",
+            ),
+            ("Mon, 05 Sep 2022 10:00:00 GMT", "2022-09-05T10:00:00+00:00"),
+            ("2022-09-05T10:00:00Z", "2022-09-05T10:00:00+00:00"),
+            (
+                "2022-09-05T10:00:00.123+02:00",
+                "2022-09-05T10:00:00.123+02:00",
+            ),
+            // time zone abbreviations and offsets
+            ("Mon, 5 Sep 2022 10:00:00 EST", "2022-09-05T10:00:00-05:00"),
+            ("Mon, 5 Sep 2022 10:00:00 PDT", "2022-09-05T10:00:00-07:00"),
+            (
+                "Mon, 05 Sep 2022 10:00:00 CEST",
+                "2022-09-05T10:00:00+02:00",
+            ),
+            (
+                "Mon, 05 Sep 2022 10:00:00 GMT+2",
+                "2022-09-05T10:00:00+02:00",
+            ),
+            (
+                "Mon, 05 Sep 2022 10:00:00 GMT+02:00",
+                "2022-09-05T10:00:00+02:00",
+            ),
+            (
+                "Mon, 05 Sep 2022 10:00:00 UTC-5",
+                "2022-09-05T10:00:00-05:00",
+            ),
+            (
+                "Mon, 05 Sep 2022 10:00:00 +0200 (CEST)",
+                "2022-09-05T10:00:00+02:00",
+            ),
+            ("2022-09-05 10:00:00 +0200", "2022-09-05T10:00:00+02:00"),
+            ("2022-09-05T10:00:00+0200", "2022-09-05T10:00:00+02:00"),
+            // wrong or missing weekday
+            (
+                "Tue, 05 Sep 2022 10:00:00 +0000",
+                "2022-09-05T10:00:00+00:00",
+            ),
+            ("05 Sep 2022 10:00:00 +0000", "2022-09-05T10:00:00+00:00"),
+            (
+                "Monday, 5 September 2022 10:00",
+                "2022-09-05T10:00:00+00:00",
+            ),
+            (
+                "Thurs, 8 Sept 2022 10:00:00 GMT",
+                "2022-09-08T10:00:00+00:00",
+            ),
+            // two-digit years
+            ("Mon, 05 Sep 22 10:00:00 +0000", "2022-09-05T10:00:00+00:00"),
+            ("05 Sep 98 10:00 GMT", "1998-09-05T10:00:00+00:00"),
+            // month first
+            ("Sep 5, 2022 10:00 AM", "2022-09-05T10:00:00+00:00"),
+            (
+                "September 5, 2022 3:30 p.m. EDT",
+                "2022-09-05T15:30:00-04:00",
+            ),
+            ("Sep 5 2022", "2022-09-05T00:00:00+00:00"),
+            // localized month names
+            (
+                "lun., 05 sept. 2022 10:00:00 +0200",
+                "2022-09-05T10:00:00+02:00",
+            ),
+            ("5 septembre 2022", "2022-09-05T00:00:00+00:00"),
+            (
+                "Mo, 05 Dez 2022 10:00:00 +0100",
+                "2022-12-05T10:00:00+01:00",
+            ),
+            ("5. März 2022", "2022-03-05T00:00:00+00:00"),
+            (
+                "lunes, 5 de septiembre de 2022 10:00",
+                "2022-09-05T10:00:00+00:00",
+            ),
+            (
+                "05 ottobre 2022 10:00:00 +0200",
+                "2022-10-05T10:00:00+02:00",
+            ),
+            (
+                "segunda-feira, 5 de setembro de 2022",
+                "2022-09-05T00:00:00+00:00",
+            ),
+            ("5 mei 2022", "2022-05-05T00:00:00+00:00"),
+            (
+                "luni, 5 septembrie 2022 10:00 EEST",
+                "2022-09-05T10:00:00+03:00",
+            ),
+            // ISO dates without time zone
+            ("2022-09-05T10:00:00", "2022-09-05T10:00:00+00:00"),
+            ("2022-09-05T10:00:00.000", "2022-09-05T10:00:00+00:00"),
+            ("2022-09-05 10:00", "2022-09-05T10:00:00+00:00"),
+            ("2022-09-05", "2022-09-05T00:00:00+00:00"),
+            ("2022/09/05", "2022-09-05T00:00:00+00:00"),
+            ("05.09.2022 10:00", "2022-09-05T10:00:00+00:00"),
+            ("  2022-09-05  ", "2022-09-05T00:00:00+00:00"),
+        ];
+
+        for (input, expected) in corpus {
+            let date = parse_date(input).map(|date| date.to_rfc3339());
+            assert_eq!(date.as_deref(), Some(expected), "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_dates() {
+        for input in [
+            "",
+            "yesterday",
+            "32 Sep 2022",
+            "2022-13-01",
+            "Sep 2022 10:00 +25",
+            "Mon, 05 Sep 2022 10:00:00 XYZ",
+        ] {
+            assert!(parse_date(input).is_none(), "parsing {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use mail_parser::{mailbox, Message, MessageParser};
+use rss::{Channel, Guid, Item};
+use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
+use std::{error::Error, fs, path::Path};
+
+use super::html::is_absolute;
+
+/// Texts of the links to the web version of newsletters.
+const WEB_VERSION_TEXTS: [&str; 6] = [
+    "in browser",
+    "in your browser",
+    "view online",
+    "read online",
+    "web version",
+    "view this email",
+];
+
+/// Reads the newsletters of a Maildir directory or an mbox file, from the path
+/// or `file://` URL of the source. Messages already processed, by Message-ID,
+/// are left out. The Message-IDs of all the messages are returned along.
+pub fn read_channel(
+    link: &str,
+    processed: &[String],
+) -> Result<(Channel, Vec<String>), Box<dyn Error>> {
+    let path = Path::new(link.strip_prefix("file://").unwrap_or(link));
+
+    let contents = match path.is_dir() {
+        true => mailbox::maildir::MessageIterator::new(path)?
+            .map(|message| message.map(|message| message.unwrap_contents()))
+            .collect::<Result<Vec<Vec<u8>>, _>>()?,
+        // the file is read first, as the mbox reader hides the IO errors, and
+        // reading from memory can't fail
+        false => mailbox::mbox::MessageIterator::new(&fs::read(path)?[..])
+            .flatten()
+            .map(|message| message.unwrap_contents())
+            .collect(),
+    };
+
+    let parser = MessageParser::default();
+    let messages = contents
+        .iter()
+        .filter_map(|content| parser.parse(content))
+        .collect::<Vec<Message>>();
+
+    let items = messages
+        .iter()
+        .filter(|message| !processed.contains(&message_id(message)))
+        .map(item)
+        .collect();
+
+    let message_ids = messages.iter().map(message_id).collect();
+
+    Ok((
+        Channel {
+            items,
+            ..Default::default()
+        },
+        message_ids,
+    ))
+}
+
+/// Returns the Message-ID of a message. Messages without one are identified by
+/// a hash of their `Date`, `From` and `Subject` headers instead.
+fn message_id(message: &Message) -> String {
+    if let Some(id) = message.message_id() {
+        return id.to_string();
+    }
+
+    let headers = ["Date", "From", "Subject"]
+        .map(|name| message.header_raw(name).unwrap_or_default().trim())
+        .join("\n");
+
+    format!("{:x}@notion-feed", Sha256::digest(headers.as_bytes()))
+}
+
+/// Converts a message to an item, identified by its Message-ID. The HTML part
+/// is the content, and the link is its web version, a "view in browser" link,
+/// or else the `mid:` URL of the message. The `List-Post` header isn't used, as
+/// it's the same for all the issues of a newsletter.
+fn item(message: &Message) -> Item {
+    let id = message_id(message);
+    let html = message.body_html(0).map(|html| html.into_owned());
+
+    let link = html
+        .as_deref()
+        .and_then(web_version)
+        .unwrap_or_else(|| format!("mid:{}", id));
+
+    let author = message
+        .from()
+        .and_then(|from| from.first())
+        .and_then(|from| {
+            from.name()
+                .or(from.address())
+                .map(|author| author.trim().to_string())
+        });
+
+    Item {
+        title: Some(message.subject().unwrap_or("(no subject)").to_string()),
+        link: Some(link),
+        author,
+        pub_date: message.date().map(|date| date.to_rfc822()),
+        content: html,
+        guid: Some(Guid {
+            value: id,
+            permalink: false,
+        }),
+        ..Default::default()
+    }
+}
+
+fn web_version(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").unwrap();
+
+    document
+        .select(&selector)
+        .find(|link| {
+            let text = link.text().collect::<String>().to_lowercase();
+            WEB_VERSION_TEXTS.iter().any(|web| text.contains(web))
+        })
+        .and_then(|link| link.value().attr("href"))
+        .filter(|href| is_absolute(href))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const MBOX: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/newsletters.mbox");
+
+    #[test]
+    fn it_reads_mbox_newsletters() {
+        let (channel, message_ids) = read_channel(MBOX, &[]).unwrap();
+        let items = &channel.items;
+
+        assert_eq!(message_ids.len(), 4);
+
+        assert_eq!(items.len(), 4);
+
+        assert_eq!(items[0].title.as_deref(), Some("This Week in Rust 563"));
+        assert_eq!(
+            items[0].link.as_deref(),
+            Some("mid:issue-563@rust-weekly.dev")
+        );
+        assert_eq!(items[0].author.as_deref(), Some("This Week in Rust"));
+        assert_eq!(
+            items[0].guid.as_ref().map(|guid| guid.value.as_str()),
+            Some("issue-563@rust-weekly.dev")
+        );
+        assert!(items[0]
+            .content
+            .as_deref()
+            .is_some_and(|html| html.contains("another issue of This Week in Rust!")));
+
+        assert_eq!(items[1].title.as_deref(), Some("Weekly digest — September"));
+        assert_eq!(
+            items[1].link.as_deref(),
+            Some("https://bereghici.dev/digest/2024-09-03?utm_source=email")
+        );
+        assert_eq!(
+            items[1].pub_date.as_deref(),
+            Some("Tue, 3 Sep 2024 08:30:00 +0200")
+        );
+
+        assert_eq!(items[2].link.as_deref(), Some("mid:welcome@bereghici.dev"));
+        assert_eq!(items[2].author.as_deref(), Some("noreply@bereghici.dev"));
+
+        // messages without Message-ID get an ID from their headers
+        assert_eq!(items[3].link, Some(format!("mid:{}", message_ids[3])));
+        assert_eq!(
+            items[3].guid.as_ref().map(|guid| guid.value.as_str()),
+            Some(message_ids[3].as_str())
+        );
+        assert!(message_ids[3].ends_with("@notion-feed"));
+    }
+
+    #[test]
+    fn it_reports_unreadable_mailboxes() {
+        let err = read_channel("/nonexistent/newsletters.mbox", &[]).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().map(|err| err.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[test]
+    fn it_skips_processed_messages() {
+        let processed = vec!["issue-563@rust-weekly.dev".to_string()];
+        let (channel, message_ids) = read_channel(&format!("file://{}", MBOX), &processed).unwrap();
+
+        assert_eq!(channel.items.len(), 3);
+        assert!(message_ids.contains(&processed[0]));
+
+        let (channel, _) = read_channel(MBOX, &message_ids).unwrap();
+        assert!(channel.items.is_empty());
+    }
+
+    #[test]
+    fn it_reads_maildir_newsletters() {
+        let path = env::temp_dir().join(format!("notion-feed-maildir-{}", std::process::id()));
+        fs::create_dir_all(path.join("cur")).unwrap();
+        fs::create_dir_all(path.join("new")).unwrap();
+        fs::write(
+            path.join("new").join("1725264000.1.bereghici"),
+            "Message-ID: <1@bereghici.dev>\r\nSubject: Hello\r\nFrom: a@bereghici.dev\r\n\r\nHi",
+        )
+        .unwrap();
+
+        let channel = read_channel(path.to_str().unwrap(), &[]);
+        fs::remove_dir_all(&path).unwrap();
+
+        let items = channel.unwrap().0.items;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Hello"));
+        assert_eq!(items[0].link.as_deref(), Some("mid:1@bereghici.dev"));
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use rss::Item;
+
+use super::{
+    calendar::EventTime, cover::item_image, date::parse_date, metadata, rules::Actions, youtube,
+};
+
+/// An item fetched from a source which isn't in the feed yet, or an event
+/// whose page is updated.
+#[derive(Debug, Default, Clone)]
+pub struct Entry {
+    /// Index of the source in the source list
+    pub source: usize,
+    pub title: String,
+    pub link: String,
+    pub pub_date: Option<DateTime<FixedOffset>>,
+    /// Items without a (valid) publication date are considered published now
+    pub created_date: DateTime<FixedOffset>,
+    pub author: Option<String>,
+    pub categories: Vec<String>,
+    /// HTML content of the item, or its description
+    pub content: Option<String>,
+    /// URL of the image used as the page cover
+    pub cover: Option<String>,
+    /// URL of the discussion about the item
+    pub comments: Option<String>,
+    pub enclosure: Option<Enclosure>,
+    /// Podcast episode duration (`itunes:duration`), in seconds or `HH:MM:SS`
+    pub duration: Option<String>,
+    /// Podcast episode number (`itunes:episode`)
+    pub episode: Option<String>,
+    /// Watch URL of a YouTube video, embedded in the page body
+    pub video: Option<String>,
+    /// View count of a video (`media:statistics`)
+    pub views: Option<u64>,
+    /// Reddit score, Hacker News points or GitHub reactions
+    pub score: Option<i64>,
+    pub comment_count: Option<u64>,
+    /// Unique identifier of the item (`guid`), the Message-ID of newsletters
+    pub guid: Option<String>,
+    /// Start and end of an event
+    pub event_date: Option<(EventTime, Option<EventTime>)>,
+    pub location: Option<String>,
+    /// Actions of the rules matching the item, set once it's known to be new
+    pub actions: Actions,
+}
+
+/// Media file attached to an item, like a podcast episode.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Enclosure {
+    pub url: String,
+    pub mime_type: String,
+    /// Size in bytes
+    pub length: Option<u64>,
+}
+
+impl Entry {
+    pub fn new(source: usize, item: Item) -> Option<Entry> {
+        let pub_date = item.pub_date.as_deref().and_then(parse_date);
+        let cover = item_image(&item);
+        let video = youtube::video_url(&item);
+        let views = youtube::views(&item);
+        let score = metadata::score(&item);
+        let comment_count = metadata::comment_count(&item);
+        let event_date = metadata::event_date(&item);
+        let location = metadata::location(&item);
+        let itunes = item.itunes_ext.unwrap_or_default();
+
+        // RSS authors are emails, the name is usually in `dc:creator`
+        let author = item
+            .dublin_core_ext
+            .as_ref()
+            .and_then(|dublin_core| dublin_core.creators.first().cloned())
+            .or(item.author);
+
+        Some(Self {
+            source,
+            title: item.title?,
+            link: item.link?,
+            pub_date,
+            created_date: pub_date.unwrap_or_else(|| Utc::now().fixed_offset()),
+            author,
+            categories: item
+                .categories
+                .into_iter()
+                .map(|category| category.name)
+                .collect(),
+            content: item.content.or(item.description),
+            cover,
+            comments: item.comments,
+            enclosure: item.enclosure.map(|enclosure| Enclosure {
+                url: enclosure.url,
+                mime_type: enclosure.mime_type,
+                length: enclosure
+                    .length
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|length| *length > 0),
+            }),
+            duration: itunes.duration,
+            episode: itunes.episode,
+            video,
+            views,
+            score,
+            comment_count,
+            guid: item.guid.map(|guid| guid.value),
+            event_date,
+            location,
+            actions: Actions::default(),
+        })
+    }
+}
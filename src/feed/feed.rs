@@ -1,15 +1,40 @@
-use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
-use futures::{future, try_join};
+use chrono::{DateTime, FixedOffset, Utc};
+use futures::{future, stream, try_join, StreamExt};
+use reqwest::Url;
 use rss::{Channel, Item};
+use serde_json::Number;
 
-use crate::notion::{
-    database::{DatabaseFilter, DatabaseKind, DatabaseQuery, Filter, FilterKind},
-    models::{Date, Page, PropertyValue, RichText, Text},
-    Client,
+use crate::{
+    config::PropertyNames,
+    notion::{
+        database::{Database, DatabaseFilter, DatabaseKind, DatabaseQuery, Filter, FilterKind},
+        models::{
+            Block, Date, ExternalFile, File, Icon, Page, PropertyValue, Relation, RichText,
+            SelectOption, Text, UrlBlock,
+        },
+        Client,
+    },
+    state::{Snapshot, State},
 };
-use std::{collections::HashMap, convert::identity, error::Error};
+use std::{cmp::Reverse, collections::HashMap, convert::identity, error::Error};
 
-use super::{feed_item::FeedItem, source::Source};
+use super::{
+    atom,
+    calendar::{self, EventTime},
+    cover::{page_icon, page_image},
+    date::parse_date,
+    email,
+    entry::{Enclosure, Entry},
+    feed_item::FeedItem,
+    github, hacker_news,
+    html::{is_absolute, to_blocks},
+    metadata, readability, reddit,
+    rules::Rules,
+    scrape,
+    source::{Source, SourceKind, LAST_ITEM_DATE},
+    summary::Summary,
+    watch, youtube,
+};
 
 pub struct Feed<'a> {
     client: &'a Client<'a>,
@@ -20,66 +45,297 @@ impl<'a> Feed<'a> {
         Self { client }
     }
 
-    pub async fn run(&self) -> Result<(), Box<dyn Error>> {
-        let (source_list, feed_list) = try_join!(self.get_source_list(), self.get_feed_list())?;
+    pub async fn run(&self) -> Result<Summary, Box<dyn Error>> {
+        let rules = Rules::load(self.client.config.rules_file.as_deref())?;
+        let mut state = State::load(self.client.config.state_file.as_deref())?;
 
-        let channel_items = future::join_all(
-            source_list
-                .into_iter()
-                .map(|source| Feed::<'_>::get_rss_items(source)),
-        )
-        .await;
+        // items routed by rules to other databases are deduplicated as well
+        let (mut source_list, feed_list, routed_feed_lists, schemas) = try_join!(
+            self.get_source_list(),
+            self.get_feed_list(DatabaseKind::Feed),
+            future::try_join_all(
+                rules
+                    .databases()
+                    .into_iter()
+                    .map(|id| self.get_feed_list(DatabaseKind::Id(id)))
+            ),
+            self.get_schemas(&rules)
+        )?;
 
-        let mut all_items = vec![];
+        for source in source_list.iter_mut() {
+            if let Some(source_state) = state.source(&source.link) {
+                source.last_item_date = source.last_item_date.max(source_state.last_item_date);
+                source.snapshot = source_state.snapshot.clone();
+                source.message_ids = source_state.message_ids.clone();
+                source.icon = source_state.icon.clone();
+            }
+        }
 
-        channel_items.iter().for_each(|item| {
-            if let Ok(item) = item {
-                all_items.extend(item);
+        let channel_items = future::join_all(source_list.iter().map(Feed::get_rss_items)).await;
+
+        let mut summary = Summary::new(&source_list);
+        let mut entries = vec![];
+        let mut snapshots = vec![None; source_list.len()];
+        let mut websites = vec![None; source_list.len()];
+        let mut mailboxes = vec![None; source_list.len()];
+        let mut excluded = vec![];
+        let mut below_score = vec![];
+
+        let feed_list = feed_list
+            .into_iter()
+            .chain(routed_feed_lists.into_iter().flatten())
+            .collect::<Vec<FeedItem>>();
+
+        for (index, items) in channel_items.into_iter().enumerate() {
+            match items {
+                Ok(source_items) => {
+                    // excluded items which are already in the feed were counted before
+                    summary.sources[index].excluded = source_items
+                        .excluded
+                        .iter()
+                        .chain(source_items.below_score.iter())
+                        .filter_map(|item| item.link.as_deref())
+                        .filter(|link| !feed_list.iter().any(|item| item.link == *link))
+                        .count();
+                    // the channel image is preferred to the favicon looked up before
+                    if source_items.image.is_some() {
+                        source_list[index].icon = source_items.image;
+                    }
+                    websites[index] = source_items.website;
+                    source_list[index].channel_title = source_items.title;
+                    snapshots[index] = source_items.snapshot;
+                    mailboxes[index] = source_items.message_ids;
+                    excluded.extend(
+                        source_items
+                            .excluded
+                            .into_iter()
+                            .filter_map(|item| Entry::new(index, item)),
+                    );
+                    below_score.extend(
+                        source_items
+                            .below_score
+                            .into_iter()
+                            .filter_map(|item| Entry::new(index, item)),
+                    );
+                    entries.extend(
+                        source_items
+                            .items
+                            .into_iter()
+                            .filter_map(|item| Entry::new(index, item)),
+                    );
+                }
+                Err(err) => eprintln!("Failed to fetch {}: {}", source_list[index].link, err),
             }
-        });
+        }
 
-        let feed_list_links = feed_list
+        let (ingested, mut new): (Vec<Entry>, Vec<Entry>) = entries
             .into_iter()
-            .map(|item| item.link)
-            .collect::<Vec<String>>();
+            .partition(|entry| feed_list.iter().any(|item| item.link == entry.link));
+
+        // events already in the feed are only read again when they changed
+        let (mut updated, mut ingested): (Vec<Entry>, Vec<Entry>) =
+            ingested.into_iter().partition(|entry| {
+                source_list[entry.source].kind == SourceKind::Calendar
+                    && feed_list
+                        .iter()
+                        .find(|item| item.link == entry.link)
+                        .is_some_and(|item| is_modified(entry, item))
+            });
+
+        // rules are applied once, their actions are carried with the entry
+        for entry in new.iter_mut().chain(updated.iter_mut()) {
+            entry.actions = rules.apply(entry, &source_list[entry.source]);
+        }
+
+        let (skipped, new): (Vec<Entry>, Vec<Entry>) =
+            new.into_iter().partition(|entry| entry.actions.skip);
+
+        // skipped events keep their page as it is
+        let (skipped_updates, updated): (Vec<Entry>, Vec<Entry>) =
+            updated.into_iter().partition(|entry| entry.actions.skip);
+
+        for entry in skipped.into_iter().chain(skipped_updates) {
+            summary.sources[entry.source].skipped += 1;
+            ingested.push(entry);
+        }
+
+        let (new, mut pending) =
+            defer_over_limit(&source_list, new, self.client.config.max_new_items);
+
+        for entry in pending.iter() {
+            summary.sources[entry.source].deferred += 1;
+        }
+
+        // favicons are only looked up for the sources with items to add, once
+        let favicons = future::join_all(source_list.iter().enumerate().map(|(index, source)| {
+            let website = websites[index]
+                .as_deref()
+                .filter(|_| source.icon.is_none())
+                .filter(|_| new.iter().any(|entry| entry.source == index));
+
+            async move {
+                match website {
+                    Some(website) => Feed::get_favicon(website).await,
+                    None => None,
+                }
+            }
+        }))
+        .await;
+
+        for (index, favicon) in favicons.into_iter().enumerate() {
+            if let Some(favicon) = favicon {
+                state.source_mut(&source_list[index].link).icon = Some(favicon.clone());
+                source_list[index].icon = Some(favicon);
+            }
+        }
+
+        // linked pages are only fetched for the items being added
+        let new = stream::iter(new)
+            .map(|entry| {
+                let source = &source_list[entry.source];
+                Feed::enrich(entry, source)
+            })
+            .buffer_unordered(MAX_LINKED_PAGE_REQUESTS)
+            .collect::<Vec<Entry>>()
+            .await;
+
+        let results = future::join_all(new.iter().map(|entry| {
+            let source = &source_list[entry.source];
+            self.add_feed_entry(entry, source, &schemas)
+        }))
+        .await;
+
+        for (entry, result) in new.into_iter().zip(results) {
+            match result {
+                Ok(_) => {
+                    summary.sources[entry.source].added += 1;
+                    ingested.push(entry);
+                }
+                Err(err) => {
+                    eprintln!("Failed to add {}: {}", entry.link, err);
+                    summary.sources[entry.source].failed += 1;
+                    pending.push(entry);
+                }
+            }
+        }
+
+        let results = future::join_all(updated.iter().map(|entry| {
+            let page = feed_list.iter().find(|item| item.link == entry.link);
+            self.update_feed_entry(entry, page, &schemas)
+        }))
+        .await;
+
+        for (entry, result) in updated.into_iter().zip(results) {
+            match result {
+                Ok(_) => {
+                    summary.sources[entry.source].updated += 1;
+                    ingested.push(entry);
+                }
+                Err(err) => {
+                    eprintln!("Failed to update {}: {}", entry.link, err);
+                    summary.sources[entry.source].failed += 1;
+                    pending.push(entry);
+                }
+            }
+        }
+
+        // excluded items aren't considered again, unless older items are pending,
+        // while items under the minimum score are until they drop out of the source
+        ingested.extend(excluded);
+        let held = pending
+            .iter()
+            .chain(below_score.iter())
+            .cloned()
+            .collect::<Vec<Entry>>();
+
+        self.update_last_item_dates(&source_list, &ingested, &held, &mut state)
+            .await;
+
+        // newsletters are processed once, even when excluded or skipped, except
+        // the ones which couldn't be added; the deleted ones are forgotten
+        for (index, message_ids) in mailboxes.into_iter().enumerate() {
+            if let Some(message_ids) = message_ids {
+                state.source_mut(&source_list[index].link).message_ids = message_ids
+                    .into_iter()
+                    .filter(|id| {
+                        !pending
+                            .iter()
+                            .any(|entry| entry.source == index && entry.guid.as_ref() == Some(id))
+                    })
+                    .collect();
+            }
+        }
+
+        // a change which couldn't be added is reported again on the next run
+        for (index, snapshot) in snapshots.into_iter().enumerate() {
+            if snapshot.is_some() && !pending.iter().any(|entry| entry.source == index) {
+                state.source_mut(&source_list[index].link).snapshot = snapshot;
+            }
+        }
+
+        state.save()?;
+
+        Ok(summary)
+    }
+
+    /// Remembers the newest ingested item of each source, so that the next run
+    /// only considers newer items. Pending items (deferred or which couldn't be
+    /// added) are considered again on the next run.
+    async fn update_last_item_dates(
+        &self,
+        source_list: &[Source],
+        ingested: &[Entry],
+        pending: &[Entry],
+        state: &mut State,
+    ) {
+        let updates = last_item_dates(source_list, ingested, pending);
+
+        for (source, last_item_date) in updates.iter() {
+            state.source_mut(&source.link).last_item_date = Some(*last_item_date);
+        }
 
         future::join_all(
-            all_items
+            updates
                 .iter()
-                .filter(|item| match &item.link {
-                    Some(link) => !feed_list_links.contains(link),
-                    None => false,
-                })
-                .map(|item| {
-                    let title = &item.title;
-                    let link = &item.link;
-                    let pub_date = &item.pub_date;
-
-                    let created_date = match pub_date {
-                        Some(pub_date) => {
-                            let pub_date =
-                                parse_date(&pub_date).unwrap_or(Local::now().date_naive());
-                            Some(Utc.from_utc_date(&pub_date).and_hms(0, 0, 0))
-                        }
-                        None => Some(Utc::now()),
-                    };
-
-                    if let (Some(title), Some(link), Some(created_date)) =
-                        (title, link, created_date)
-                    {
-                        return Some(self.add_feed_entry(
-                            title.to_string(),
-                            link.to_string(),
-                            created_date,
-                        ));
+                .filter(|(source, _)| source.has_last_item_date)
+                .map(|(source, last_item_date)| async move {
+                    let properties = HashMap::from([(
+                        LAST_ITEM_DATE.to_string(),
+                        PropertyValue::Date {
+                            date: Some(Date::new(*last_item_date, None)),
+                        },
+                    )]);
+
+                    if let Err(err) = self.client.update_page(&source.page_id, properties).await {
+                        eprintln!("Failed to update {}: {}", source.link, err);
                     }
-                    None
-                })
-                .filter_map(identity),
+                }),
         )
         .await;
+    }
 
-        Ok(())
+    /// Returns the properties of the feed database and of the databases where
+    /// rules route items, by database ID.
+    async fn get_schemas(
+        &self,
+        rules: &Rules,
+    ) -> Result<HashMap<String, Database>, Box<dyn Error>> {
+        let kinds = std::iter::once(DatabaseKind::Feed)
+            .chain(rules.databases().into_iter().map(DatabaseKind::Id))
+            .collect::<Vec<DatabaseKind>>();
+
+        let schemas = future::try_join_all(
+            kinds
+                .iter()
+                .map(|kind| self.client.retrieve_database(kind.clone())),
+        )
+        .await?;
+
+        Ok(kinds
+            .iter()
+            .map(|kind| kind.id(self.client.config).to_string())
+            .zip(schemas)
+            .collect())
     }
 
     pub async fn get_source_list(&self) -> Result<Vec<Source>, Box<dyn Error>> {
@@ -93,27 +349,53 @@ impl<'a> Feed<'a> {
             )]),
         };
 
-        let query = DatabaseQuery {
-            start_cursor: None,
-            page_size: None,
-            sorts: None,
-            filter: Some(filter),
-        };
+        let pages = self.query_pages(DatabaseKind::Source, Some(filter)).await?;
 
-        let pages = self
-            .client
-            .query_database(DatabaseKind::Source, Some(query))
-            .await?
-            .results;
+        return Ok(pages
+            .iter()
+            .filter_map(|page| match Source::new(page) {
+                // changes and processed newsletters are only known from the state file
+                Ok(source)
+                    if matches!(source.kind, SourceKind::Watch | SourceKind::Email)
+                        && self.client.config.state_file.is_none() =>
+                {
+                    eprintln!(
+                        "Skipping source: {:?} needs a state file (--state-file)",
+                        source.link
+                    );
+                    None
+                }
+                Ok(source) => Some(source),
+                Err(err) => {
+                    eprintln!("Skipping source: {}", err);
+                    None
+                }
+            })
+            .collect::<Vec<Source>>());
+    }
+
+    pub async fn get_feed_list(&self, kind: DatabaseKind) -> Result<Vec<FeedItem>, Box<dyn Error>> {
+        let pages = self.get_pages(kind).await?;
 
         return Ok(pages
             .iter()
-            .map(|page| return Source::new(page))
+            .map(|page| return FeedItem::new(page))
             .filter_map(identity)
-            .collect::<Vec<Source>>());
+            .collect::<Vec<FeedItem>>());
     }
 
-    pub async fn get_feed_list(&self) -> Result<Vec<FeedItem>, Box<dyn Error>> {
+    /// Returns all the pages of a database, following the pagination.
+    pub async fn get_pages(&self, kind: DatabaseKind) -> Result<Vec<Page>, Box<dyn Error>> {
+        self.query_pages(kind, None).await
+    }
+
+    /// Returns all the pages of a database matching a filter, following the
+    /// pagination.
+    async fn query_pages(
+        &self,
+        kind: DatabaseKind,
+        filter: Option<DatabaseFilter>,
+    ) -> Result<Vec<Page>, Box<dyn Error>> {
         let mut pages = vec![];
         let mut cursor: Option<String> = None;
 
@@ -124,13 +406,13 @@ impl<'a> Feed<'a> {
                     None => None,
                 },
                 page_size: None, // use default value (100)
-                filter: None,
+                filter: filter.clone(),
                 sorts: None,
             };
 
             let current_pages = self
                 .client
-                .query_database(DatabaseKind::Feed, Some(query))
+                .query_database(kind.clone(), Some(query))
                 .await?;
 
             pages.extend(current_pages.results);
@@ -141,111 +423,1114 @@ impl<'a> Feed<'a> {
             }
         }
 
-        return Ok(pages
-            .iter()
-            .map(|page| return FeedItem::new(page))
-            .filter_map(identity)
-            .collect::<Vec<FeedItem>>());
+        Ok(pages)
     }
 
     pub async fn add_feed_entry(
         &self,
-        title: String,
-        link: String,
-        created_time: DateTime<Utc>,
+        entry: &Entry,
+        source: &Source,
+        schemas: &HashMap<String, Database>,
     ) -> Result<Page, Box<dyn Error>> {
-        let page_props = HashMap::from([
+        let actions = &entry.actions;
+
+        let mut page_props = HashMap::from([
             (
                 "Title".to_string(),
                 PropertyValue::Title {
                     title: vec![RichText::Text {
                         rich_text: None,
                         text: Text {
-                            content: title,
+                            content: entry.title.to_string(),
                             link: None,
                         },
+                        annotations: None,
                     }],
                 },
             ),
-            ("Link".to_string(), PropertyValue::Url { url: Some(link) }),
+            (
+                "Link".to_string(),
+                PropertyValue::Url {
+                    url: Some(entry.link.to_string()),
+                },
+            ),
             (
                 "Read".to_string(),
                 PropertyValue::Checkbox { checkbox: false },
             ),
             (
                 "Starred".to_string(),
-                PropertyValue::Checkbox { checkbox: false },
+                PropertyValue::Checkbox {
+                    checkbox: actions.starred,
+                },
             ),
             (
                 "Published At".to_string(),
                 PropertyValue::Date {
-                    date: Some(Date {
-                        start: Some(created_time),
-                        end: None,
-                    }),
+                    date: Some(Date::new(entry.created_date, self.client.config.timezone)),
                 },
             ),
         ]);
 
-        let result = self
+        let kind = actions.feed_database();
+        let schema = schemas.get(kind.id(self.client.config));
+        page_props.extend(item_properties(
+            entry,
+            source,
+            &self.client.config.property_names,
+            schema,
+        ));
+
+        page_props.extend(self.event_properties(entry, schema));
+
+        let mut children = entry_blocks(entry);
+        let overflow = children.split_off(Block::fit(&children));
+
+        let cover = entry.cover.as_ref().map(|url| File::External {
+            external: ExternalFile {
+                url: url.to_string(),
+            },
+        });
+
+        let icon = feed_page_icon(source, self.client.config.icon_emoji.as_deref());
+
+        let page = self
             .client
-            .create_page(DatabaseKind::Feed, page_props)
-            .await;
+            .create_page(kind, page_props, children, cover, icon)
+            .await?;
 
-        Ok(result?)
+        // a page with part of its body is removed, so that it's added again whole
+        if !overflow.is_empty() {
+            if let Err(err) = self.client.append_block_children(&page.id, overflow).await {
+                if let Err(err) = self.client.archive_page(&page.id).await {
+                    eprintln!("Failed to remove {}: {}", entry.link, err);
+                }
+
+                return Err(err);
+            }
+        }
+
+        Ok(page)
     }
 
-    pub async fn get_rss_items(source: Source) -> Result<Vec<Item>, Box<dyn Error>> {
-        let content = reqwest::get(&source.link).await?.bytes().await?;
-        let channel = Channel::read_from(&content[..])?;
+    /// Updates the page of an event which changed since it was added: its
+    /// title, date range, location and body. The body replaces all the blocks
+    /// of the page, including the ones added by hand. `Published At`, which
+    /// tells whether the event changed, is written last, so that the page is
+    /// updated again on the next run when the body couldn't be replaced.
+    async fn update_feed_entry(
+        &self,
+        entry: &Entry,
+        page: Option<&FeedItem>,
+        schemas: &HashMap<String, Database>,
+    ) -> Result<(), Box<dyn Error>> {
+        let page = page.ok_or("No page")?;
+        let kind = entry.actions.feed_database();
+
+        let mut page_props = self.event_properties(entry, schemas.get(kind.id(self.client.config)));
+        page_props.insert(
+            "Title".to_string(),
+            PropertyValue::Title {
+                title: RichText::plain(&entry.title),
+            },
+        );
 
-        let offset_date = source.offset_date;
+        self.client.update_page(&page.id, page_props).await?;
+        self.client.delete_block_children(&page.id).await?;
+        self.client
+            .append_block_children(&page.id, entry_blocks(entry))
+            .await?;
 
-        if let Some(offset_date) = offset_date {
-            let items = channel
-                .items
-                .into_iter()
-                .filter(|item| {
-                    let pub_date = item.pub_date.as_ref();
+        let published_at = HashMap::from([(
+            "Published At".to_string(),
+            PropertyValue::Date {
+                date: Some(Date::new(entry.created_date, self.client.config.timezone)),
+            },
+        )]);
+        self.client.update_page(&page.id, published_at).await?;
 
-                    if let Some(pub_date) = pub_date {
-                        let pub_date = parse_date(pub_date);
+        Ok(())
+    }
+
+    /// Returns the date range and location of an event, as the properties the
+    /// database has.
+    fn event_properties(
+        &self,
+        entry: &Entry,
+        schema: Option<&Database>,
+    ) -> HashMap<String, PropertyValue> {
+        let names = &self.client.config.property_names;
+        let property_type = |name: &str| {
+            schema
+                .filter(|_| !name.is_empty())
+                .and_then(|schema| schema.property_type(name))
+        };
 
-                        if let Some(pub_date) = pub_date {
-                            return pub_date.ge(&offset_date);
-                        }
+        let mut page_props = HashMap::new();
 
-                        return false;
-                    }
+        // floating times are read in the time zone of the feed
+        let time_zone = self.client.config.timezone;
+        let event_date = entry.event_date.and_then(|(start, end)| {
+            let end = end.and_then(|end| end.in_time_zone(time_zone));
 
-                    return true;
-                })
-                .collect();
+            match start {
+                EventTime::Day(start) => Some(Date::days(start, end.map(|end| end.date_naive()))),
+                start => Some(Date::range(start.in_time_zone(time_zone)?, end, time_zone)),
+            }
+        });
+
+        if let (Some("date"), Some(date)) = (property_type(&names.event_date), event_date) {
+            page_props.insert(
+                names.event_date.to_string(),
+                PropertyValue::Date { date: Some(date) },
+            );
+        }
+
+        if let Some(property) = property_type(&names.location)
+            .zip(entry.location.as_deref())
+            .and_then(|(property_type, location)| value_property(property_type, location))
+        {
+            page_props.insert(names.location.to_string(), property);
+        }
+
+        page_props
+    }
+
+    /// Completes the entry with its linked page: the article replaces the feed
+    /// content for sources with `Full text`, and the page image is used as cover
+    /// for sources with `Page cover` when the item has none.
+    async fn enrich(mut entry: Entry, source: &Source) -> Entry {
+        let needs_cover = source.page_cover && entry.cover.is_none();
+
+        if !source.full_text && !needs_cover {
+            return entry;
+        }
+
+        let (html, url) = match Feed::get_linked_page(&entry.link).await {
+            Ok(page) => page,
+            Err(err) => {
+                if source.full_text {
+                    eprintln!("Failed to fetch the article {}: {}", entry.link, err);
+                }
+                return entry;
+            }
+        };
+
+        if source.full_text {
+            match readability::extract(&html, &url) {
+                Some(content) => entry.content = Some(content),
+                None => eprintln!("No article found in {}", entry.link),
+            }
+        }
+
+        if needs_cover {
+            entry.cover = page_image(&html, &url);
+        }
+
+        entry
+    }
+
+    /// Returns the HTML of the page linked by an item, and its URL after redirects.
+    pub async fn get_linked_page(link: &str) -> Result<(String, Url), Box<dyn Error>> {
+        let res = reqwest::get(link).await?.error_for_status()?;
+        let url = res.url().clone();
+
+        Ok((res.text().await?, url))
+    }
+
+    pub async fn get_rss_items(source: &Source) -> Result<SourceItems, Box<dyn Error>> {
+        let (mut channel, snapshot, message_ids) = Feed::get_channel(source).await?;
+
+        let (items, excluded): (Vec<Item>, Vec<Item>) = channel
+            .items
+            .drain(..)
+            .filter(|item| is_recent(source, item))
+            .partition(|item| source.accepts(&item_texts(item)));
+
+        let (items, below_score): (Vec<Item>, Vec<Item>) = items
+            .into_iter()
+            .partition(|item| source.accepts_score(metadata::score(item)));
+
+        let title = Some(channel.title.trim())
+            .filter(|title| !title.is_empty())
+            .map(str::to_string);
+
+        Ok(SourceItems {
+            items,
+            excluded,
+            below_score,
+            image: channel_image(&channel),
+            website: Some(channel.link.trim())
+                .filter(|link| !link.is_empty())
+                .map(str::to_string),
+            title,
+            snapshot,
+            message_ids,
+        })
+    }
+
+    /// Fetches the items of a source, depending on its type. Items of the other
+    /// sources than feeds are read from their API or page as RSS items. The
+    /// snapshot of watched pages, or the Message-IDs of the mailbox of
+    /// newsletters, are returned along.
+    async fn get_channel(
+        source: &Source,
+    ) -> Result<(Channel, Option<Snapshot>, Option<Vec<String>>), Box<dyn Error>> {
+        let channel = match source.kind {
+            SourceKind::Feed => {
+                let link = youtube::feed_link(&source.link).unwrap_or_else(|| source.link.clone());
+                let content = reqwest::get(&link).await?.bytes().await?;
+
+                match Channel::read_from(&content[..]) {
+                    Err(rss::Error::InvalidStartTag) => atom::read_channel(&content[..])?,
+                    channel => channel?,
+                }
+            }
+            SourceKind::Reddit => {
+                reddit::read_channel(&Feed::get_api(&reddit::api_url(&source.link)?).await?)?
+            }
+            SourceKind::HackerNews => hacker_news::read_channel(
+                &Feed::get_api(&hacker_news::api_url(&source.link)?).await?,
+            )?,
+            SourceKind::GitHub => {
+                let repository = github::Repository::new(&source.link)?;
+                repository.read_channel(&Feed::get_api(&repository.api_url()).await?)?
+            }
+            SourceKind::Scrape => {
+                let selectors = source.selectors.as_ref().ok_or("No selectors")?;
+                let (html, url) = Feed::get_linked_page(&source.link).await?;
+
+                scrape::read_channel(&html, &url, selectors)
+            }
+            SourceKind::Watch => {
+                let (html, url) = Feed::get_linked_page(&source.link).await?;
+                let (channel, snapshot) = watch::read_channel(
+                    &html,
+                    &url,
+                    source.name.as_deref(),
+                    source.selector.as_ref(),
+                    source.snapshot.as_ref(),
+                    Utc::now(),
+                );
+
+                return Ok((channel, Some(snapshot), None));
+            }
+            SourceKind::Email => {
+                let (channel, message_ids) =
+                    email::read_channel(&source.link, &source.message_ids)?;
+
+                return Ok((channel, None, Some(message_ids)));
+            }
+            SourceKind::Calendar => {
+                // `webcal://` links are served over HTTPS
+                let link = match source.link.strip_prefix("webcal://") {
+                    Some(link) => format!("https://{}", link),
+                    None => source.link.clone(),
+                };
+                let content = reqwest::get(&link)
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?;
 
-            return Ok(items);
+                calendar::read_channel(&content, &source.link)?
+            }
+        };
+
+        Ok((channel, None, None))
+    }
+
+    /// Requests an API, which may reject clients without a user agent.
+    async fn get_api(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let content = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()?
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(content.to_vec())
+    }
+
+    /// Returns the icon of a website, or its favicon.
+    async fn get_favicon(website: &str) -> Option<String> {
+        let (html, url) = Feed::get_linked_page(website).await.ok()?;
+
+        if let Some(icon) = page_icon(&html, &url) {
+            return Some(icon);
+        }
+
+        let favicon = url.join("/favicon.ico").ok()?;
+        reqwest::get(favicon.clone())
+            .await
+            .ok()?
+            .error_for_status()
+            .ok()?;
+
+        Some(favicon.to_string())
+    }
+}
+
+const USER_AGENT: &str = concat!("notion-feed/", env!("CARGO_PKG_VERSION"));
+
+/// Number of linked pages fetched at the same time
+const MAX_LINKED_PAGE_REQUESTS: usize = 8;
+
+/// File types played by Notion audio and video blocks
+const AUDIO_EXTENSIONS: [&str; 5] = ["mp3", "wav", "ogg", "oga", "m4a"];
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "m4v", "mov", "webm", "mkv", "avi", "wmv"];
+
+/// Recent items of a source accepted by its keywords.
+pub struct SourceItems {
+    pub items: Vec<Item>,
+    /// Recent items excluded by the keywords
+    pub excluded: Vec<Item>,
+    /// Recent items under the minimum score, which they may still reach
+    pub below_score: Vec<Item>,
+    /// Channel image or podcast artwork
+    pub image: Option<String>,
+    /// Website of the channel, whose favicon is used without image
+    pub website: Option<String>,
+    /// Title of the channel
+    pub title: Option<String>,
+    /// Text of watched pages
+    pub snapshot: Option<Snapshot>,
+    /// Message-IDs of all the messages of newsletters
+    pub message_ids: Option<Vec<String>>,
+}
+
+/// Returns the channel image or podcast artwork.
+fn channel_image(channel: &Channel) -> Option<String> {
+    channel
+        .image
+        .as_ref()
+        .map(|image| image.url.trim())
+        .filter(|url| is_absolute(url))
+        .or_else(|| {
+            channel
+                .itunes_ext
+                .as_ref()
+                .and_then(|itunes| itunes.image.as_deref())
+                .map(str::trim)
+                .filter(|url| is_absolute(url))
+        })
+        .map(str::to_string)
+}
+
+/// Returns the optional properties of a feed page which the database has, with
+/// the type they have in its schema.
+fn item_properties(
+    entry: &Entry,
+    source: &Source,
+    names: &PropertyNames,
+    schema: Option<&Database>,
+) -> HashMap<String, PropertyValue> {
+    let actions = &entry.actions;
+    let mut page_props = HashMap::new();
+
+    // optional properties are left out when the database doesn't have them
+    let property_type = |name: &str| {
+        schema
+            .filter(|_| !name.is_empty())
+            .and_then(|schema| schema.property_type(name))
+    };
+
+    let mut tags = actions
+        .tags
+        .iter()
+        .map(|tag| SelectOption::new(tag))
+        .collect::<Vec<SelectOption>>();
+
+    if property_type(&names.tags) == Some("multi_select") {
+        for category in entry.categories.iter() {
+            let category = SelectOption::new(category);
+
+            if !tags.contains(&category) {
+                tags.push(category);
+            }
+        }
+    }
+
+    if !tags.is_empty() && property_type(&names.tags) == Some("multi_select") {
+        page_props.insert(
+            names.tags.to_string(),
+            PropertyValue::MultiSelect { multi_select: tags },
+        );
+    }
+
+    if let Some(priority) = actions
+        .priority
+        .as_deref()
+        .filter(|_| property_type("Priority") == Some("select"))
+    {
+        page_props.insert(
+            "Priority".to_string(),
+            PropertyValue::Select {
+                select: Some(SelectOption::new(priority)),
+            },
+        );
+    }
+
+    if let Some(source_property) = property_type(&names.source)
+        .and_then(|property_type| source_property(property_type, source))
+    {
+        page_props.insert(names.source.to_string(), source_property);
+    }
+
+    let enclosure = entry.enclosure.as_ref();
+
+    // number properties hold the duration in seconds
+    let duration = match property_type(&names.duration) {
+        Some("number") => entry
+            .duration
+            .as_deref()
+            .and_then(duration_seconds)
+            .map(|seconds| seconds.to_string()),
+        _ => entry.duration.clone(),
+    };
+
+    let values = [
+        (&names.author, entry.author.clone()),
+        (&names.comments, entry.comments.clone()),
+        (
+            &names.enclosure,
+            enclosure.map(|enclosure| enclosure.url.clone()),
+        ),
+        (
+            &names.media_type,
+            enclosure.map(|enclosure| enclosure.mime_type.clone()),
+        ),
+        (
+            &names.size,
+            enclosure
+                .and_then(|enclosure| enclosure.length)
+                .map(|length| length.to_string()),
+        ),
+        (&names.duration, duration),
+        (&names.episode, entry.episode.clone()),
+        (&names.views, entry.views.map(|views| views.to_string())),
+        (&names.score, entry.score.map(|score| score.to_string())),
+        (
+            &names.comment_count,
+            entry.comment_count.map(|count| count.to_string()),
+        ),
+    ];
+
+    for (name, value) in values {
+        if let Some(property) = property_type(name)
+            .zip(value)
+            .and_then(|(property_type, value)| value_property(property_type, &value))
+        {
+            page_props.insert(name.to_string(), property);
+        }
+    }
+
+    page_props
+}
+
+/// Returns the icon of a feed page: the favicon of its source, or the emoji of
+/// the config for sources without one.
+fn feed_page_icon(source: &Source, icon_emoji: Option<&str>) -> Option<Icon> {
+    match (&source.icon, icon_emoji) {
+        (Some(url), _) => Some(Icon::External {
+            external: ExternalFile {
+                url: url.to_string(),
+            },
+        }),
+        (None, Some(emoji)) => Some(Icon::Emoji {
+            emoji: emoji.to_string(),
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Links a feed page to its source, depending on the type of the `Source`
+/// property: a relation to the source page, or the channel title.
+fn source_property(property_type: &str, source: &Source) -> Option<PropertyValue> {
+    let title = source
+        .channel_title
+        .as_deref()
+        .or(source.name.as_deref())
+        .unwrap_or(&source.link);
+
+    match property_type {
+        "relation" => Some(PropertyValue::Relation {
+            relation: vec![Relation {
+                id: source.page_id.to_string(),
+            }],
+        }),
+        "select" | "rich_text" => value_property(property_type, title),
+        _ => None,
+    }
+}
+
+/// Converts a value to a property of the given type, if it fits.
+fn value_property(property_type: &str, value: &str) -> Option<PropertyValue> {
+    match property_type {
+        "rich_text" => Some(PropertyValue::Text {
+            rich_text: RichText::plain(value),
+        }),
+        "select" => Some(PropertyValue::Select {
+            select: Some(SelectOption::new(value)),
+        }),
+        "url" => Some(PropertyValue::Url {
+            url: Some(value.to_string()),
+        }),
+        "number" => Some(PropertyValue::Number {
+            number: Some(Number::from_f64(value.trim().parse::<f64>().ok()?)?),
+        }),
+        _ => None,
+    }
+}
+
+/// Parses podcast durations: `3600`, `60:00` or `1:00:00`.
+fn duration_seconds(duration: &str) -> Option<u64> {
+    let parts = duration.trim().split(':').collect::<Vec<&str>>();
+
+    if parts.len() > 3 {
+        return None;
+    }
+
+    parts.into_iter().try_fold(0u64, |seconds, part| {
+        seconds
+            .checked_mul(60)?
+            .checked_add(part.parse::<u64>().ok()?)
+    })
+}
+
+/// Returns the page body of an entry: its content, after the embedded video or
+/// enclosure.
+fn entry_blocks(entry: &Entry) -> Vec<Block> {
+    let mut children = entry.content.as_deref().map(to_blocks).unwrap_or_default();
+
+    if let Some(block) = entry.enclosure.as_ref().and_then(enclosure_block) {
+        children.insert(0, block);
+    }
+
+    if let Some(url) = &entry.video {
+        children.insert(
+            0,
+            Block::Video {
+                video: File::External {
+                    external: ExternalFile {
+                        url: url.to_string(),
+                    },
+                },
+            },
+        );
+    }
+
+    children
+}
+
+/// Embeds audio and video enclosures, like podcast episodes, in the page body.
+/// Notion only plays some file types, the others are linked as bookmarks.
+fn enclosure_block(enclosure: &Enclosure) -> Option<Block> {
+    let url = Url::parse(&enclosure.url).ok()?;
+    let extension = url
+        .path()
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    let file = File::External {
+        external: ExternalFile {
+            url: url.to_string(),
+        },
+    };
+
+    let block = match enclosure.mime_type.split('/').next() {
+        Some("audio") if AUDIO_EXTENSIONS.contains(&extension.as_str()) => {
+            Block::Audio { audio: file }
+        }
+        Some("video") if VIDEO_EXTENSIONS.contains(&extension.as_str()) => {
+            Block::Video { video: file }
+        }
+        Some("audio" | "video") => Block::Bookmark {
+            bookmark: UrlBlock {
+                url: url.to_string(),
+                caption: vec![],
+            },
+        },
+        _ => return None,
+    };
+
+    Some(block)
+}
+
+/// Keeps the newest items within the `Max items` of each source and the global
+/// budget of new items. Returns the kept items and the deferred ones.
+fn defer_over_limit(
+    source_list: &[Source],
+    mut entries: Vec<Entry>,
+    max_new_items: Option<usize>,
+) -> (Vec<Entry>, Vec<Entry>) {
+    entries.sort_by_key(|entry| Reverse(entry.created_date));
+
+    let mut counts = vec![0; source_list.len()];
+
+    let (mut kept, mut deferred): (Vec<Entry>, Vec<Entry>) =
+        entries.into_iter().partition(|entry| {
+            counts[entry.source] += 1;
+
+            source_list[entry.source]
+                .max_items
+                .is_none_or(|max_items| counts[entry.source] <= max_items)
+        });
+
+    if let Some(max_new_items) = max_new_items {
+        if kept.len() > max_new_items {
+            deferred.extend(kept.split_off(max_new_items));
+        }
+    }
+
+    (kept, deferred)
+}
+
+/// Returns the sources whose newest ingested item is newer than their last item
+/// date, with the date of this item. Items newer than a pending item are left
+/// out, so that the pending item is considered again on the next run.
+fn last_item_dates<'s>(
+    source_list: &'s [Source],
+    ingested: &[Entry],
+    pending: &[Entry],
+) -> Vec<(&'s Source, DateTime<FixedOffset>)> {
+    source_list
+        .iter()
+        .enumerate()
+        .filter_map(|(index, source)| {
+            let oldest_pending = pending
+                .iter()
+                .filter(|entry| entry.source == index)
+                .filter_map(|entry| entry.pub_date)
+                .min();
+
+            let last_item_date = ingested
+                .iter()
+                .filter(|entry| entry.source == index)
+                .filter_map(|entry| entry.pub_date)
+                .filter(|pub_date| oldest_pending.is_none_or(|pending| *pub_date < pending))
+                .max()?
+                // a future-dated item must not hide the items published until then
+                .min(Utc::now().fixed_offset());
+
+            (Some(last_item_date) > source.last_item_date).then_some((source, last_item_date))
+        })
+        .collect()
+}
+
+/// Checks whether an event was modified since its page was added or updated,
+/// by its last modification kept as `Published At`. Events without one are
+/// never updated.
+fn is_modified(entry: &Entry, page: &FeedItem) -> bool {
+    match (entry.pub_date, page.published_at) {
+        // Notion may drop the seconds of the dates
+        (Some(pub_date), Some(published_at)) => {
+            pub_date.timestamp() / 60 > published_at.timestamp() / 60
         }
+        _ => false,
+    }
+}
+
+/// Texts matched by the `Include` and `Exclude` keywords of a source.
+fn item_texts(item: &Item) -> Vec<&str> {
+    [item.title.as_deref(), item.description.as_deref()]
+        .into_iter()
+        .flatten()
+        .chain(
+            item.categories
+                .iter()
+                .map(|category| category.name.as_str()),
+        )
+        .collect()
+}
 
-        Ok(channel.items)
+/// Checks the item against the source offset date and the newest item ingested
+/// by a previous run. Items without a publication date can't be checked, so
+/// they are kept and deduplicated by link.
+fn is_recent(source: &Source, item: &Item) -> bool {
+    let pub_date = match item.pub_date.as_deref() {
+        Some(pub_date) => parse_date(pub_date),
+        None => return true,
+    };
+
+    // newsletters are tracked by Message-ID, as messages delivered late or
+    // stored out of order may be older than the newest one
+    let last_item_date = match source.kind {
+        SourceKind::Email => None,
+        _ => source.last_item_date,
+    };
+
+    match pub_date {
+        Some(pub_date) => {
+            source
+                .offset_date
+                .is_none_or(|offset_date| pub_date.date_naive() >= offset_date)
+                && last_item_date.is_none_or(|last_item_date| pub_date > last_item_date)
+        }
+        None => source.offset_date.is_none() && last_item_date.is_none(),
     }
 }
 
-fn parse_date(input: &String) -> Option<NaiveDate> {
-    let rfc2822 = DateTime::parse_from_rfc2822(&input);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notion::database::PropertySchema;
+    use chrono::NaiveDate;
+    use rss::{extension::itunes::ITunesChannelExtension, Category, Image};
 
-    if let Ok(rfc2822) = rfc2822 {
-        return Some(rfc2822.date_naive());
+    fn source(max_items: Option<usize>) -> Source {
+        Source {
+            page_id: "page".to_string(),
+            link: "https://bereghici.dev/rss".to_string(),
+            max_items,
+            ..Default::default()
+        }
     }
 
-    let rfc3339 = DateTime::parse_from_rfc3339(&input);
+    fn entry(source: usize, day: u32) -> Entry {
+        let pub_date = DateTime::parse_from_rfc3339(&format!("2022-09-{:02}T10:00:00Z", day)).ok();
 
-    if let Ok(rfc3339) = rfc3339 {
-        return Some(rfc3339.date_naive());
+        Entry {
+            source,
+            title: format!("Item {}", day),
+            link: format!("https://bereghici.dev/{}/{}", source, day),
+            pub_date,
+            created_date: pub_date.unwrap(),
+            ..Default::default()
+        }
     }
 
-    let date_only = NaiveDate::parse_from_str(&input, "%Y-%m-%d");
-    if let Ok(date_only) = date_only {
-        return Some(date_only);
+    fn titles(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.title.as_str()).collect()
     }
 
-    None
+    #[test]
+    fn it_matches_keywords_against_title_description_and_categories() {
+        let mut source = source(None);
+        source.include = "rust".parse().ok();
+        source.exclude = "/^sponsored$/".parse().ok();
+
+        let mut item = Item::default();
+        item.set_title("Weekly issue".to_string());
+        assert!(!source.accepts(&item_texts(&item)));
+
+        item.set_description("All about Rust".to_string());
+        assert!(source.accepts(&item_texts(&item)));
+
+        item.set_categories(vec![Category {
+            name: "sponsored".to_string(),
+            domain: None,
+        }]);
+        assert!(!source.accepts(&item_texts(&item)));
+    }
+
+    #[test]
+    fn it_keeps_older_unprocessed_newsletters() {
+        let mut source = source(None);
+        source.last_item_date = entry(0, 10).pub_date;
+
+        let mut item = Item::default();
+        item.set_pub_date("Mon, 05 Sep 2022 10:00:00 +0000".to_string());
+        assert!(!is_recent(&source, &item));
+
+        source.kind = SourceKind::Email;
+        assert!(is_recent(&source, &item));
+
+        source.offset_date = NaiveDate::from_ymd_opt(2022, 9, 6);
+        assert!(!is_recent(&source, &item));
+    }
+
+    #[test]
+    fn it_defers_items_over_source_limit() {
+        let source_list = vec![source(Some(2)), source(None)];
+        let entries = vec![entry(0, 1), entry(0, 3), entry(0, 2), entry(1, 4)];
+
+        let (kept, deferred) = defer_over_limit(&source_list, entries, None);
+
+        assert_eq!(titles(&kept), vec!["Item 4", "Item 3", "Item 2"]);
+        assert_eq!(titles(&deferred), vec!["Item 1"]);
+    }
+
+    #[test]
+    fn it_defers_oldest_items_over_budget() {
+        let source_list = vec![source(None), source(Some(1))];
+        let entries = vec![
+            entry(0, 1),
+            entry(1, 5),
+            entry(0, 3),
+            entry(1, 4),
+            entry(0, 2),
+        ];
+
+        let (kept, deferred) = defer_over_limit(&source_list, entries, Some(2));
+
+        assert_eq!(titles(&kept), vec!["Item 5", "Item 3"]);
+        assert_eq!(titles(&deferred), vec!["Item 4", "Item 2", "Item 1"]);
+    }
+
+    #[test]
+    fn it_moves_last_item_date_past_ingested_items_until_pending_ones() {
+        let source_list = vec![source(None), source(None)];
+        // the excluded items of a run are ingested as well
+        let ingested = vec![entry(0, 1), entry(0, 3), entry(1, 2), entry(1, 5)];
+        let pending = vec![entry(1, 4)];
+
+        let dates = last_item_dates(&source_list, &ingested, &pending)
+            .into_iter()
+            .map(|(source, date)| (source.link.as_str(), date))
+            .collect::<Vec<(&str, DateTime<FixedOffset>)>>();
+
+        assert_eq!(
+            dates,
+            vec![
+                (source_list[0].link.as_str(), entry(0, 3).pub_date.unwrap()),
+                (source_list[1].link.as_str(), entry(1, 2).pub_date.unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_updates_events_modified_since_added() {
+        let mut page = FeedItem {
+            id: "page".to_string(),
+            link: "https://bereghici.dev/0/5".to_string(),
+            published_at: entry(0, 4).pub_date,
+        };
+
+        assert!(is_modified(&entry(0, 5), &page));
+        assert!(!is_modified(&entry(0, 4), &page));
+        assert!(!is_modified(&entry(0, 3), &page));
+
+        page.published_at = None;
+        assert!(!is_modified(&entry(0, 5), &page));
+    }
+
+    #[test]
+    fn it_links_items_to_their_source() {
+        let mut source = source(None);
+
+        assert_eq!(
+            source_property("relation", &source),
+            Some(PropertyValue::Relation {
+                relation: vec![Relation {
+                    id: "page".to_string()
+                }]
+            })
+        );
+        assert_eq!(
+            source_property("rich_text", &source),
+            Some(PropertyValue::Text {
+                rich_text: RichText::plain("https://bereghici.dev/rss")
+            })
+        );
+
+        source.channel_title = Some("Alexandru, Bereghici".to_string());
+        assert_eq!(
+            source_property("select", &source),
+            Some(PropertyValue::Select {
+                select: Some(SelectOption::new("Alexandru  Bereghici"))
+            })
+        );
+        assert_eq!(source_property("number", &source), None);
+    }
+
+    #[test]
+    fn it_maps_item_properties_to_the_schema() {
+        let schema = |properties: &[(&str, &str)]| Database {
+            id: "feed".to_string(),
+            properties: properties
+                .iter()
+                .map(|(name, property_type)| {
+                    (
+                        name.to_string(),
+                        PropertySchema {
+                            id: name.to_string(),
+                            property_type: property_type.to_string(),
+                        },
+                    )
+                })
+                .collect(),
+        };
+        let entry = Entry {
+            author: Some("Alexandru Bereghici".to_string()),
+            categories: vec!["Rust, async".to_string(), "News".to_string()],
+            comments: Some("https://bereghici.dev/post#comments".to_string()),
+            ..entry(0, 1)
+        };
+        let mut names = PropertyNames::default();
+
+        let properties = item_properties(
+            &entry,
+            &source(None),
+            &names,
+            Some(&schema(&[
+                ("Author", "rich_text"),
+                ("Tags", "multi_select"),
+                ("Comments", "url"),
+            ])),
+        );
+        assert_eq!(
+            properties.get("Author"),
+            Some(&PropertyValue::Text {
+                rich_text: RichText::plain("Alexandru Bereghici")
+            })
+        );
+        assert_eq!(
+            properties.get("Tags"),
+            Some(&PropertyValue::MultiSelect {
+                multi_select: vec![SelectOption::new("Rust  async"), SelectOption::new("News")]
+            })
+        );
+        assert_eq!(
+            properties.get("Comments"),
+            Some(&PropertyValue::Url {
+                url: Some("https://bereghici.dev/post#comments".to_string())
+            })
+        );
+
+        names.author = String::new();
+        let properties = item_properties(
+            &entry,
+            &source(None),
+            &names,
+            Some(&schema(&[("Author", "rich_text"), ("Tags", "select")])),
+        );
+        assert!(properties.is_empty());
+        assert!(item_properties(&entry, &source(None), &names, None).is_empty());
+    }
+
+    #[test]
+    fn it_falls_back_to_podcast_artwork() {
+        let mut channel = Channel::default();
+        channel.set_image(Image {
+            url: "/images/logo.png".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(channel_image(&channel), None);
+
+        channel.set_itunes_ext(ITunesChannelExtension {
+            image: Some("https://cdn.bereghici.dev/artwork.jpg".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            channel_image(&channel),
+            Some("https://cdn.bereghici.dev/artwork.jpg".to_string())
+        );
+
+        channel.set_image(Image {
+            url: "https://bereghici.dev/images/logo.png".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(
+            channel_image(&channel),
+            Some("https://bereghici.dev/images/logo.png".to_string())
+        );
+    }
+
+    #[test]
+    fn it_prefers_source_favicons_to_the_icon_emoji() {
+        let mut source = source(None);
+
+        assert_eq!(feed_page_icon(&source, None), None);
+        assert_eq!(
+            feed_page_icon(&source, Some("📰")),
+            Some(Icon::Emoji {
+                emoji: "📰".to_string()
+            })
+        );
+
+        source.icon = Some("https://bereghici.dev/favicon.ico".to_string());
+        assert_eq!(
+            feed_page_icon(&source, Some("📰")),
+            Some(Icon::External {
+                external: ExternalFile {
+                    url: "https://bereghici.dev/favicon.ico".to_string()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn it_parses_podcast_durations() {
+        assert_eq!(duration_seconds("1:02:03"), Some(3723));
+        assert_eq!(duration_seconds("45:30"), Some(2730));
+        assert_eq!(duration_seconds("3600"), Some(3600));
+        assert_eq!(duration_seconds("1h"), None);
+        assert_eq!(duration_seconds("1:2:3:4"), None);
+        assert_eq!(duration_seconds("18446744073709551615:00"), None);
+    }
+
+    #[test]
+    fn it_embeds_enclosures() {
+        let enclosure = |url: &str, mime_type: &str| Enclosure {
+            url: url.to_string(),
+            mime_type: mime_type.to_string(),
+            length: None,
+        };
+        let file = |url: &str| File::External {
+            external: ExternalFile {
+                url: url.to_string(),
+            },
+        };
+
+        assert_eq!(
+            enclosure_block(&enclosure(
+                "https://cdn.bereghici.dev/episode.MP3?source=rss",
+                "audio/mpeg"
+            )),
+            Some(Block::Audio {
+                audio: file("https://cdn.bereghici.dev/episode.MP3?source=rss")
+            })
+        );
+        assert_eq!(
+            enclosure_block(&enclosure(
+                "https://cdn.bereghici.dev/episode",
+                "audio/mpeg"
+            )),
+            Some(Block::Bookmark {
+                bookmark: UrlBlock {
+                    url: "https://cdn.bereghici.dev/episode".to_string(),
+                    caption: vec![],
+                }
+            })
+        );
+        assert_eq!(
+            enclosure_block(&enclosure(
+                "https://cdn.bereghici.dev/talk.mp4",
+                "video/mp4"
+            )),
+            Some(Block::Video {
+                video: file("https://cdn.bereghici.dev/talk.mp4")
+            })
+        );
+        assert_eq!(
+            enclosure_block(&enclosure(
+                "https://cdn.bereghici.dev/cover.png",
+                "image/png"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn it_converts_values_to_properties() {
+        assert_eq!(
+            value_property("number", "42"),
+            Some(PropertyValue::Number {
+                number: Number::from_f64(42.0)
+            })
+        );
+        assert_eq!(value_property("number", "forty-two"), None);
+        assert_eq!(
+            value_property("url", "https://news.ycombinator.com/item?id=1"),
+            Some(PropertyValue::Url {
+                url: Some("https://news.ycombinator.com/item?id=1".to_string())
+            })
+        );
+        assert_eq!(value_property("checkbox", "true"), None);
+    }
 }
@@ -1,8 +1,14 @@
-use crate::notion::models::{Page, PropertyValue};
+use chrono::{DateTime, FixedOffset};
+
+use crate::notion::models::{Date, Page, PropertyValue};
 
 #[derive(Debug)]
 pub struct FeedItem {
+    /// ID of the page
+    pub id: String,
     pub link: String,
+    /// Publication date of the item, the last modification of events
+    pub published_at: Option<DateTime<FixedOffset>>,
 }
 
 impl FeedItem {
@@ -17,8 +23,19 @@ impl FeedItem {
             _ => None,
         };
 
+        let published_at = match properties.get("Published At") {
+            Some(PropertyValue::Date {
+                date: Some(Date { start, .. }),
+            }) => *start,
+            _ => None,
+        };
+
         if let Some(link) = link {
-            return Some(Self { link });
+            return Some(Self {
+                id: page.id.to_string(),
+                link,
+                published_at,
+            });
         }
 
         None
@@ -0,0 +1,387 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use reqwest::Url;
+use rss::{Category, Channel, Item};
+use serde::Deserialize;
+use std::error::Error;
+
+use super::{date::parse_date, html::from_text, metadata};
+
+/// Characters of tag names which aren't allowed in the path of release links.
+/// Slashes are kept, like in the links of GitHub.
+const TAG_NAME: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    html_url: String,
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    draft: bool,
+    prerelease: bool,
+    published_at: Option<String>,
+    author: Option<User>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    html_url: String,
+    number: u64,
+    title: String,
+    body: Option<String>,
+    user: Option<User>,
+    created_at: String,
+    comments: u64,
+    #[serde(default)]
+    labels: Vec<Label>,
+    reactions: Option<Reactions>,
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reactions {
+    total_count: i64,
+}
+
+/// What is followed in a repository.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Target {
+    Releases,
+    Tags,
+    /// Issues, with a label or all of them
+    Issues(Option<String>),
+}
+
+/// A GitHub repository followed as a source, from its link:
+/// `https://github.com/owner/repo` or `.../releases` for releases, `.../tags`
+/// for tags, and `.../labels/<label>` or `.../issues?labels=<label>` for issues.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Repository {
+    pub owner: String,
+    pub name: String,
+    pub target: Target,
+}
+
+impl Repository {
+    pub fn new(link: &str) -> Result<Repository, String> {
+        let url = Url::parse(link).map_err(|err| format!("{:?} is not a URL: {}", link, err))?;
+
+        if !matches!(url.host_str(), Some("github.com" | "www.github.com")) {
+            return Err(format!("{:?} is not a GitHub URL", link));
+        }
+
+        let segments = url
+            .path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_else(Vec::new);
+
+        let target = match segments.get(2..).unwrap_or_default() {
+            [] | ["releases"] => Target::Releases,
+            ["tags"] => Target::Tags,
+            ["issues"] => Target::Issues(
+                url.query_pairs()
+                    .find(|(key, _)| key == "labels")
+                    .map(|(_, label)| label.to_string()),
+            ),
+            // path segments are percent-encoded, unlike the query pairs
+            ["labels", label] => Target::Issues(Some(
+                percent_decode_str(label).decode_utf8_lossy().to_string(),
+            )),
+            _ => {
+                return Err(format!(
+                    "{:?} is not a GitHub repository, or its releases, tags or issues",
+                    link
+                ))
+            }
+        };
+
+        match segments[..] {
+            [owner, name, ..] => Ok(Self {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                target,
+            }),
+            _ => Err(format!("{:?} is not a GitHub repository", link)),
+        }
+    }
+
+    /// Returns the REST API request listing the followed items.
+    pub fn api_url(&self) -> String {
+        let mut url = Url::parse(&format!(
+            "https://api.github.com/repos/{}/{}",
+            self.owner, self.name
+        ))
+        .unwrap();
+
+        match &self.target {
+            Target::Releases => url.set_path(&format!("{}/releases", url.path())),
+            Target::Tags => url.set_path(&format!("{}/tags", url.path())),
+            Target::Issues(label) => {
+                url.set_path(&format!("{}/issues", url.path()));
+                url.query_pairs_mut()
+                    .append_pair("state", "all")
+                    .append_pair("sort", "created");
+
+                if let Some(label) = label {
+                    url.query_pairs_mut().append_pair("labels", label);
+                }
+            }
+        }
+
+        url.to_string()
+    }
+
+    /// Reads the response of the REST API as items. Draft releases and pull
+    /// requests are left out.
+    pub fn read_channel(&self, content: &[u8]) -> Result<Channel, Box<dyn Error>> {
+        let repository = format!("{}/{}", self.owner, self.name);
+        let link = format!("https://github.com/{}", repository);
+
+        let items = match &self.target {
+            Target::Releases => serde_json::from_slice::<Vec<Release>>(content)?
+                .into_iter()
+                .filter(|release| !release.draft)
+                .map(|release| Item {
+                    title: Some(format!(
+                        "{} {}",
+                        repository,
+                        release
+                            .name
+                            .filter(|name| !name.trim().is_empty())
+                            .unwrap_or(release.tag_name)
+                    )),
+                    link: Some(release.html_url),
+                    description: release.body.as_deref().map(from_text),
+                    author: release.author.map(|author| author.login),
+                    categories: release
+                        .prerelease
+                        .then(|| Category {
+                            name: "Pre-release".to_string(),
+                            domain: None,
+                        })
+                        .into_iter()
+                        .collect(),
+                    pub_date: release
+                        .published_at
+                        .as_deref()
+                        .and_then(parse_date)
+                        .map(|date| date.to_rfc2822()),
+                    ..Default::default()
+                })
+                .collect(),
+            // tags have no date, they are deduplicated by link
+            Target::Tags => serde_json::from_slice::<Vec<Tag>>(content)?
+                .into_iter()
+                .map(|tag| Item {
+                    title: Some(format!("{} {}", repository, tag.name)),
+                    link: Some(format!(
+                        "{}/releases/tag/{}",
+                        link,
+                        utf8_percent_encode(&tag.name, TAG_NAME)
+                    )),
+                    ..Default::default()
+                })
+                .collect(),
+            Target::Issues(_) => serde_json::from_slice::<Vec<Issue>>(content)?
+                .into_iter()
+                .filter(|issue| issue.pull_request.is_none())
+                .map(|issue| {
+                    let mut item = Item {
+                        title: Some(format!("{}#{} {}", repository, issue.number, issue.title)),
+                        link: Some(issue.html_url),
+                        description: issue.body.as_deref().map(from_text),
+                        author: issue.user.map(|user| user.login),
+                        categories: issue
+                            .labels
+                            .into_iter()
+                            .map(|label| Category {
+                                name: label.name,
+                                domain: None,
+                            })
+                            .collect(),
+                        pub_date: parse_date(&issue.created_at).map(|date| date.to_rfc2822()),
+                        ..Default::default()
+                    };
+
+                    metadata::set_comment_count(&mut item, issue.comments);
+
+                    if let Some(reactions) = issue.reactions {
+                        metadata::set_score(&mut item, reactions.total_count);
+                    }
+
+                    item
+                })
+                .collect(),
+        };
+
+        Ok(Channel {
+            title: repository,
+            link,
+            items,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(target: Target) -> Repository {
+        Repository {
+            owner: "rust-lang".to_string(),
+            name: "rust".to_string(),
+            target,
+        }
+    }
+
+    #[test]
+    fn it_reads_releases() {
+        let channel = repository(Target::Releases)
+            .read_channel(include_bytes!("../../fixtures/github_releases.json"))
+            .unwrap();
+
+        let titles = channel
+            .items
+            .iter()
+            .filter_map(|item| item.title.as_deref())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            titles,
+            vec!["rust-lang/rust 2024-09-30", "rust-lang/rust nightly"]
+        );
+
+        let item = &channel.items[0];
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://github.com/rust-lang/rust-analyzer/releases/tag/2024-09-30")
+        );
+        assert_eq!(
+            item.description.as_deref(),
+            Some("<p>Commit: `f5c1a2b`</p><p>Release notes:<br>- faster completions<br>- fewer false positives</p>")
+        );
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Mon, 30 Sep 2024 00:14:03 +0000")
+        );
+        assert_eq!(channel.items[1].categories[0].name, "Pre-release");
+    }
+
+    #[test]
+    fn it_reads_tags() {
+        let channel = repository(Target::Tags)
+            .read_channel(include_bytes!("../../fixtures/github_tags.json"))
+            .unwrap();
+
+        assert_eq!(channel.items.len(), 2);
+        assert_eq!(
+            channel.items[0].link.as_deref(),
+            Some("https://github.com/rust-lang/rust/releases/tag/1.81.0")
+        );
+        assert_eq!(channel.items[0].pub_date, None);
+
+        let channel = repository(Target::Tags)
+            .read_channel(br#"[{ "name": "nightly/2024 #1?" }]"#)
+            .unwrap();
+
+        assert_eq!(
+            channel.items[0].link.as_deref(),
+            Some("https://github.com/rust-lang/rust/releases/tag/nightly/2024%20%231%3F")
+        );
+    }
+
+    #[test]
+    fn it_reads_issues() {
+        let channel = repository(Target::Issues(Some("E-easy".to_string())))
+            .read_channel(include_bytes!("../../fixtures/github_issues.json"))
+            .unwrap();
+
+        assert_eq!(channel.items.len(), 1);
+
+        let item = &channel.items[0];
+        assert_eq!(
+            item.title.as_deref(),
+            Some("rust-lang/rust#130800 ICE: `unexpected region` in borrowck")
+        );
+        assert_eq!(item.author.as_deref(), Some("matthiaskrgr"));
+        assert_eq!(item.categories.len(), 2);
+        assert_eq!(metadata::comment_count(item), Some(3));
+        assert_eq!(metadata::score(item), Some(5));
+    }
+
+    #[test]
+    fn it_parses_repository_links() {
+        let target = |link: &str| Repository::new(link).map(|repository| repository.target);
+
+        assert_eq!(
+            Repository::new("https://github.com/rust-lang/rust"),
+            Ok(repository(Target::Releases))
+        );
+        assert_eq!(
+            target("https://github.com/rust-lang/rust/tags"),
+            Ok(Target::Tags)
+        );
+        assert_eq!(
+            target("https://github.com/rust-lang/rust/labels/E-easy"),
+            Ok(Target::Issues(Some("E-easy".to_string())))
+        );
+        assert_eq!(
+            target("https://github.com/rust-lang/rust/issues?labels=I-ICE"),
+            Ok(Target::Issues(Some("I-ICE".to_string())))
+        );
+        assert!(Repository::new("https://github.com/rust-lang").is_err());
+        assert!(Repository::new("https://github.com/rust-lang/rust/pulls").is_err());
+        assert!(Repository::new("https://gitlab.com/rust-lang/rust").is_err());
+
+        assert_eq!(
+            repository(Target::Issues(Some("E-easy".to_string()))).api_url(),
+            "https://api.github.com/repos/rust-lang/rust/issues?state=all&sort=created&labels=E-easy"
+        );
+    }
+
+    #[test]
+    fn it_decodes_labels_with_several_words() {
+        let repository =
+            Repository::new("https://github.com/rust-lang/rust/labels/good%20first%20issue")
+                .unwrap();
+
+        assert_eq!(
+            repository.target,
+            Target::Issues(Some("good first issue".to_string()))
+        );
+        assert_eq!(
+            repository.api_url(),
+            "https://api.github.com/repos/rust-lang/rust/issues?state=all&sort=created&labels=good+first+issue"
+        );
+        assert_eq!(
+            Repository::new("https://github.com/rust-lang/rust/issues?labels=good+first+issue")
+                .unwrap()
+                .target,
+            Target::Issues(Some("good first issue".to_string()))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use reqwest::Url;
+use rss::{Channel, Item};
+use serde::Deserialize;
+use std::error::Error;
+
+use super::{date::parse_date, metadata};
+
+const SEARCH_API: &str = "https://hn.algolia.com/api/v1";
+
+#[derive(Debug, Deserialize)]
+struct Search {
+    hits: Vec<Hit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    #[serde(rename = "objectID")]
+    object_id: String,
+    title: Option<String>,
+    url: Option<String>,
+    author: Option<String>,
+    created_at: String,
+    points: Option<i64>,
+    num_comments: Option<u64>,
+    story_text: Option<String>,
+}
+
+/// Returns the search API request for a Hacker News page: the front page
+/// (`https://news.ycombinator.com`), the newest, Show HN and Ask HN stories, or
+/// a search (`https://hn.algolia.com/?q=rust`), newest first.
+pub fn api_url(link: &str) -> Result<String, String> {
+    let url = Url::parse(link).map_err(|err| format!("{:?} is not a URL: {}", link, err))?;
+
+    let (endpoint, query, tags) = match (url.host_str(), url.path().trim_end_matches('/')) {
+        (Some("news.ycombinator.com"), "" | "/news" | "/front") => ("search", None, "front_page"),
+        (Some("news.ycombinator.com"), "/newest") => ("search_by_date", None, "story"),
+        (Some("news.ycombinator.com"), "/show" | "/shownew") => ("search_by_date", None, "show_hn"),
+        (Some("news.ycombinator.com"), "/ask") => ("search_by_date", None, "ask_hn"),
+        (Some("hn.algolia.com"), _) => (
+            "search_by_date",
+            url.query_pairs()
+                .find(|(key, _)| key == "q" || key == "query")
+                .map(|(_, query)| query.to_string()),
+            "story",
+        ),
+        _ => return Err(format!("{:?} is not a Hacker News page or search", link)),
+    };
+
+    let mut api_url = Url::parse(&format!("{}/{}", SEARCH_API, endpoint)).unwrap();
+    api_url.query_pairs_mut().append_pair("tags", tags);
+
+    if let Some(query) = query {
+        api_url.query_pairs_mut().append_pair("query", &query);
+    }
+
+    Ok(api_url.to_string())
+}
+
+/// Reads the stories of a search as items. Stories link to their article, or
+/// to their discussion when they have none, like Ask HN.
+pub fn read_channel(content: &[u8]) -> Result<Channel, Box<dyn Error>> {
+    let search = serde_json::from_slice::<Search>(content)?;
+
+    let items = search
+        .hits
+        .into_iter()
+        .map(|hit| {
+            let comments = format!("https://news.ycombinator.com/item?id={}", hit.object_id);
+
+            let mut item = Item {
+                title: hit.title,
+                link: hit.url.or(Some(comments.clone())),
+                description: hit.story_text,
+                author: hit.author,
+                comments: Some(comments),
+                pub_date: parse_date(&hit.created_at).map(|date| date.to_rfc2822()),
+                ..Default::default()
+            };
+
+            if let Some(points) = hit.points {
+                metadata::set_score(&mut item, points);
+            }
+            if let Some(num_comments) = hit.num_comments {
+                metadata::set_comment_count(&mut item, num_comments);
+            }
+
+            item
+        })
+        .collect();
+
+    Ok(Channel {
+        title: "Hacker News".to_string(),
+        link: "https://news.ycombinator.com".to_string(),
+        items,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_stories() {
+        let channel = read_channel(include_bytes!("../../fixtures/hacker_news.json")).unwrap();
+
+        let item = &channel.items[0];
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://blog.rust-lang.org/2024/09/05/Rust-1.81.0.html")
+        );
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://news.ycombinator.com/item?id=41449296")
+        );
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Thu, 5 Sep 2024 15:20:11 +0000")
+        );
+        assert_eq!(metadata::score(item), Some(352));
+        assert_eq!(metadata::comment_count(item), Some(184));
+
+        let item = &channel.items[1];
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://news.ycombinator.com/item?id=41450001")
+        );
+        assert!(item
+            .description
+            .as_deref()
+            .is_some_and(|text| text.starts_with("What are you using")));
+    }
+
+    #[test]
+    fn it_finds_searches() {
+        assert_eq!(
+            api_url("https://news.ycombinator.com/").as_deref(),
+            Ok("https://hn.algolia.com/api/v1/search?tags=front_page")
+        );
+        assert_eq!(
+            api_url("https://news.ycombinator.com/show").as_deref(),
+            Ok("https://hn.algolia.com/api/v1/search_by_date?tags=show_hn")
+        );
+        assert_eq!(
+            api_url("https://hn.algolia.com/?q=rust+wasm").as_deref(),
+            Ok("https://hn.algolia.com/api/v1/search_by_date?tags=story&query=rust+wasm")
+        );
+        assert!(api_url("https://news.ycombinator.com/item?id=1").is_err());
+    }
+}
@@ -0,0 +1,576 @@
+use scraper::{node::Node, ElementRef, Html};
+use std::mem;
+
+use crate::notion::models::{
+    Annotations, Block, CodeBlock, EmptyBlock, ExternalFile, File, RichText, TextBlock,
+};
+
+/// Converts the HTML content of a feed item to Notion blocks: paragraphs,
+/// headings, lists, quotes, code, dividers and images, with bold, italic, strikethrough,
+/// underline, inline code and links in their text.
+///
+/// The blocks respect the limits of the Notion API, except the number of
+/// blocks at the top level which is handled by appending them in batches.
+pub fn to_blocks(html: &str) -> Vec<Block> {
+    let fragment = Html::parse_fragment(html);
+    let mut converter = Converter::default();
+
+    converter.convert_children(fragment.root_element(), &Style::default());
+    converter.flush_text();
+    converter.blocks
+}
+
+/// Converts plain text, like descriptions of videos or releases, to HTML
+/// paragraphs. Single line breaks are kept.
+pub fn from_text(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape(paragraph).replace('\n', "<br>")))
+        .collect()
+}
+
+/// Formatting of the text being converted, inherited from its parents.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Style {
+    annotations: Annotations,
+    link: Option<String>,
+}
+
+impl Style {
+    /// Returns the style of the children of an element.
+    fn inherit(&self, element: ElementRef) -> Style {
+        let mut style = self.clone();
+
+        match element.value().name() {
+            "b" | "strong" => style.annotations.bold = true,
+            "i" | "em" | "cite" => style.annotations.italic = true,
+            "s" | "del" | "strike" => style.annotations.strikethrough = true,
+            "u" | "ins" => style.annotations.underline = true,
+            "code" | "kbd" | "samp" => style.annotations.code = true,
+            "a" => {
+                style.link = element
+                    .value()
+                    .attr("href")
+                    .filter(|href| is_absolute(href))
+                    .map(str::to_string)
+            }
+            _ => {}
+        }
+
+        style
+    }
+}
+
+/// Inline text split in segments with the same style.
+#[derive(Default)]
+struct Segments(Vec<(String, Style)>);
+
+impl Segments {
+    fn push_text(&mut self, text: &str, style: &Style) {
+        let text = collapse_whitespace(text);
+
+        let text = match self.0.last() {
+            Some((last, _)) if !last.ends_with([' ', '\n']) => text.as_str(),
+            _ => text.trim_start(),
+        };
+
+        if text.is_empty() {
+            return;
+        }
+
+        match self.0.last_mut() {
+            Some((last, last_style)) if last_style == style => last.push_str(text),
+            _ => self.0.push((text.to_string(), style.clone())),
+        }
+    }
+
+    fn push_break(&mut self) {
+        if let Some((last, _)) = self.0.last_mut() {
+            last.truncate(last.trim_end().len());
+            last.push('\n');
+        }
+    }
+
+    fn take(&mut self) -> Vec<RichText> {
+        let mut segments = mem::take(&mut self.0);
+
+        if let Some((last, _)) = segments.last_mut() {
+            last.truncate(last.trim_end().len());
+        }
+
+        segments
+            .into_iter()
+            .filter(|(text, _)| !text.is_empty())
+            .flat_map(|(text, style)| {
+                let annotations =
+                    (style.annotations != Annotations::default()).then_some(style.annotations);
+
+                RichText::new(&text, annotations, style.link)
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Converter {
+    blocks: Vec<Block>,
+    /// Inline text waiting for the next block element
+    segments: Segments,
+}
+
+impl Converter {
+    fn convert_children(&mut self, element: ElementRef, style: &Style) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.segments.push_text(text, style),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.convert_element(child, style);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn convert_element(&mut self, element: ElementRef, style: &Style) {
+        match element.value().name() {
+            "p" => {
+                self.push_text_block(element, style, paragraph);
+                self.push_images(element);
+            }
+            "h1" => self.push_text_block(element, style, |heading_1| Block::Heading1 { heading_1 }),
+            "h2" => self.push_text_block(element, style, |heading_2| Block::Heading2 { heading_2 }),
+            "h3" | "h4" | "h5" | "h6" => {
+                self.push_text_block(element, style, |heading_3| Block::Heading3 { heading_3 })
+            }
+            "blockquote" => {
+                self.push_text_block(element, style, |quote| Block::Quote { quote });
+                self.push_images(element);
+            }
+            "pre" => {
+                self.flush_text();
+
+                let rich_text = RichText::plain(element.text().collect::<String>().trim_end());
+
+                for chunk in rich_text.chunks(RichText::MAX_OBJECTS) {
+                    self.blocks.push(Block::Code {
+                        code: CodeBlock {
+                            rich_text: chunk.to_vec(),
+                            language: "plain text".to_string(),
+                        },
+                    });
+                }
+            }
+            "ul" | "ol" => {
+                self.flush_text();
+                let items = list_items(element, 1);
+                self.blocks.extend(items);
+            }
+            "img" => {
+                self.flush_text();
+                self.blocks.extend(image(element));
+            }
+            "hr" => {
+                self.flush_text();
+                self.blocks.push(Block::Divider {
+                    divider: EmptyBlock::default(),
+                });
+            }
+            "br" => self.segments.push_break(),
+            "script" | "style" | "iframe" | "noscript" => {}
+            name if is_container(name) => {
+                self.flush_text();
+                self.convert_children(element, style);
+                self.flush_text();
+            }
+            _ => self.convert_children(element, &style.inherit(element)),
+        }
+    }
+
+    fn push_text_block(
+        &mut self,
+        element: ElementRef,
+        style: &Style,
+        block: fn(TextBlock) -> Block,
+    ) {
+        self.flush_text();
+
+        let mut segments = Segments::default();
+        inline_text(element, style, &mut segments);
+
+        self.blocks.extend(text_blocks(segments.take(), block));
+    }
+
+    /// Images are blocks in Notion, so images inside text blocks follow them.
+    fn push_images(&mut self, element: ElementRef) {
+        for descendant in element.descendants().filter_map(ElementRef::wrap) {
+            if descendant.value().name() == "img" {
+                self.blocks.extend(image(descendant));
+            }
+        }
+    }
+
+    fn flush_text(&mut self) {
+        self.blocks
+            .extend(text_blocks(self.segments.take(), paragraph));
+    }
+}
+
+/// Creates a text block, followed by paragraphs with the text which doesn't
+/// fit in one block.
+fn text_blocks(rich_text: Vec<RichText>, block: fn(TextBlock) -> Block) -> Vec<Block> {
+    rich_text
+        .chunks(RichText::MAX_OBJECTS)
+        .enumerate()
+        .map(|(index, chunk)| match index {
+            0 => block(TextBlock::new(chunk.to_vec())),
+            _ => paragraph(TextBlock::new(chunk.to_vec())),
+        })
+        .collect()
+}
+
+fn paragraph(paragraph: TextBlock) -> Block {
+    Block::Paragraph { paragraph }
+}
+
+/// Collects the text of an element, with line breaks between its blocks.
+fn inline_text(element: ElementRef, style: &Style, segments: &mut Segments) {
+    match element.value().name() {
+        "br" => segments.push_break(),
+        "script" | "style" | "iframe" | "noscript" => {}
+        _ => {
+            let style = style.inherit(element);
+
+            for child in element.children() {
+                match child.value() {
+                    Node::Text(text) => segments.push_text(text, &style),
+                    Node::Element(_) => {
+                        if let Some(child) = ElementRef::wrap(child) {
+                            let name = child.value().name();
+                            let is_block = is_container(name)
+                                || matches!(name, "p" | "li" | "blockquote" | "ul" | "ol");
+
+                            if is_block {
+                                segments.push_break();
+                            }
+                            inline_text(child, &style, segments);
+                            if is_block {
+                                segments.push_break();
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Converts the items of a list. Lists nested deeper than Notion accepts in a
+/// request are flattened into their parent list, and the nested items which
+/// don't fit in their parent follow it.
+fn list_items(list: ElementRef, depth: usize) -> Vec<Block> {
+    let ordered = list.value().name() == "ol";
+    let mut blocks = vec![];
+
+    for item in list
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|item| item.value().name() == "li")
+    {
+        let mut segments = Segments::default();
+        let mut children = vec![];
+
+        for child in item.children() {
+            match ElementRef::wrap(child) {
+                Some(child) if matches!(child.value().name(), "ul" | "ol") => {
+                    children.extend(list_items(child, depth + 1))
+                }
+                Some(child) => inline_text(child, &Style::default(), &mut segments),
+                None => {
+                    if let Node::Text(text) = child.value() {
+                        segments.push_text(text, &Style::default())
+                    }
+                }
+            }
+        }
+
+        let mut rich_text = segments.take();
+        let overflow = rich_text.split_off(rich_text.len().min(RichText::MAX_OBJECTS));
+        let mut list_item = TextBlock::new(rich_text);
+
+        if depth < Block::MAX_DEPTH {
+            let rest = children.split_off(children.len().min(Block::MAX_CHILDREN));
+            list_item.children = mem::replace(&mut children, rest);
+        }
+
+        blocks.push(match ordered {
+            true => Block::NumberedListItem {
+                numbered_list_item: list_item,
+            },
+            false => Block::BulletedListItem {
+                bulleted_list_item: list_item,
+            },
+        });
+        blocks.extend(text_blocks(overflow, paragraph));
+        blocks.extend(children);
+    }
+
+    blocks
+}
+
+fn image(element: ElementRef) -> Option<Block> {
+    let src = element.value().attr("src").filter(|src| is_absolute(src))?;
+
+    Some(Block::Image {
+        image: File::External {
+            external: ExternalFile {
+                url: src.to_string(),
+            },
+        },
+    })
+}
+
+/// Notion only accepts links, images, covers and icons with absolute URLs.
+pub(super) fn is_absolute(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Escapes text for HTML content and attribute values.
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Elements separating their content from the surrounding text.
+fn is_container(name: &str) -> bool {
+    matches!(
+        name,
+        "div"
+            | "section"
+            | "article"
+            | "figure"
+            | "figcaption"
+            | "header"
+            | "footer"
+            | "main"
+            | "aside"
+            | "table"
+            | "tbody"
+            | "thead"
+            | "tr"
+            | "td"
+            | "th"
+    )
+}
+
+/// Replaces whitespace sequences with a single space, like browsers do.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char.is_whitespace() {
+            true if collapsed.ends_with(' ') => {}
+            true => collapsed.push(' '),
+            false => collapsed.push(char),
+        }
+    }
+
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notion::models::{Link, Text};
+
+    fn text_block(text: &str) -> TextBlock {
+        TextBlock::new(RichText::plain(text))
+    }
+
+    fn list_item(text: &str, children: Vec<Block>) -> Block {
+        Block::BulletedListItem {
+            bulleted_list_item: TextBlock {
+                rich_text: RichText::plain(text),
+                children,
+            },
+        }
+    }
+
+    #[test]
+    fn it_converts_html_to_blocks() {
+        let blocks = to_blocks(
+            r#"
+            Intro text
+            <h2>Title</h2>
+            <p>First
+               paragraph <img src="https://bereghici.dev/image.png"></p>
+            <ul><li>One</li><li>Two<ol><li>Nested</li></ol></li></ul>
+            <blockquote><p>Quote</p><p>continued</p></blockquote>
+            <pre>fn main() {
+    println!("hi");
+}</pre>
+            <hr>
+            <div><img src="/relative.png"><script>alert(1)</script>Last<br>line</div>
+            "#,
+        );
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Paragraph {
+                    paragraph: text_block("Intro text"),
+                },
+                Block::Heading2 {
+                    heading_2: text_block("Title"),
+                },
+                Block::Paragraph {
+                    paragraph: text_block("First paragraph"),
+                },
+                Block::Image {
+                    image: File::External {
+                        external: ExternalFile {
+                            url: "https://bereghici.dev/image.png".to_string(),
+                        },
+                    },
+                },
+                list_item("One", vec![]),
+                list_item(
+                    "Two",
+                    vec![Block::NumberedListItem {
+                        numbered_list_item: text_block("Nested"),
+                    }]
+                ),
+                Block::Quote {
+                    quote: text_block("Quote\ncontinued"),
+                },
+                Block::Code {
+                    code: CodeBlock {
+                        rich_text: RichText::plain("fn main() {\n    println!(\"hi\");\n}"),
+                        language: "plain text".to_string(),
+                    },
+                },
+                Block::Divider {
+                    divider: EmptyBlock::default(),
+                },
+                Block::Paragraph {
+                    paragraph: text_block("Last\nline"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_converts_inline_formatting() {
+        let blocks = to_blocks(
+            r#"<p>Read <b>the <i>new</i></b> <a href="https://bereghici.dev">post</a>,
+            run <code>cargo test</code> <a href="/relative">here</a></p>"#,
+        );
+
+        let text =
+            |content: &str, annotations: Option<Annotations>, link: Option<&str>| RichText::Text {
+                rich_text: None,
+                text: Text {
+                    content: content.to_string(),
+                    link: link.map(|url| Link {
+                        url: url.to_string(),
+                    }),
+                },
+                annotations,
+            };
+
+        assert_eq!(
+            blocks,
+            vec![Block::Paragraph {
+                paragraph: TextBlock::new(vec![
+                    text("Read ", None, None),
+                    text(
+                        "the ",
+                        Some(Annotations {
+                            bold: true,
+                            ..Default::default()
+                        }),
+                        None
+                    ),
+                    text(
+                        "new",
+                        Some(Annotations {
+                            bold: true,
+                            italic: true,
+                            ..Default::default()
+                        }),
+                        None
+                    ),
+                    text(" ", None, None),
+                    text("post", None, Some("https://bereghici.dev")),
+                    text(", run ", None, None),
+                    text(
+                        "cargo test",
+                        Some(Annotations {
+                            code: true,
+                            ..Default::default()
+                        }),
+                        None
+                    ),
+                    text(" here", None, None),
+                ]),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_respects_notion_limits() {
+        let blocks = to_blocks(&format!("<p>{}</p>", "a".repeat(RichText::MAX_LENGTH + 1)));
+        assert!(matches!(
+            &blocks[..],
+            [Block::Paragraph { paragraph }] if paragraph.rich_text.len() == 2
+        ));
+
+        let blocks = to_blocks("<ul><li>1<ul><li>2<ul><li>3</li></ul></li></ul></li></ul>");
+        assert_eq!(
+            blocks,
+            vec![list_item(
+                "1",
+                vec![list_item("2", vec![]), list_item("3", vec![])]
+            )]
+        );
+    }
+
+    #[test]
+    fn it_splits_blocks_over_notion_limits() {
+        let text = "<b>a</b> b ".repeat(RichText::MAX_OBJECTS);
+        let blocks = to_blocks(&format!("<h2>{}</h2>", text));
+        assert!(matches!(
+            &blocks[..],
+            [Block::Heading2 { heading_2 }, Block::Paragraph { paragraph }]
+                if heading_2.rich_text.len() == RichText::MAX_OBJECTS
+                    && paragraph.rich_text.len() == RichText::MAX_OBJECTS
+        ));
+
+        let items = "<li>2</li>".repeat(Block::MAX_CHILDREN + 1);
+        let blocks = to_blocks(&format!("<ul><li>1<ul>{}</ul></li></ul>", items));
+        assert_eq!(blocks.len(), 2);
+        assert!(matches!(
+            &blocks[0],
+            Block::BulletedListItem { bulleted_list_item }
+                if bulleted_list_item.children.len() == Block::MAX_CHILDREN
+        ));
+        assert_eq!(blocks[1], list_item("2", vec![]));
+    }
+
+    #[test]
+    fn it_converts_plain_text() {
+        assert_eq!(
+            to_blocks("Just a teaser &amp; nothing else"),
+            vec![Block::Paragraph {
+                paragraph: text_block("Just a teaser & nothing else"),
+            }]
+        );
+        assert!(to_blocks("  ").is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use regex::Regex;
+use std::str::FromStr;
+
+#[derive(Debug)]
+enum Pattern {
+    /// Matched case-insensitively anywhere in the text
+    Keyword(String),
+    Regex(Regex),
+}
+
+/// Value of the `Include` and `Exclude` properties of a source: keywords or
+/// `/regexes/` separated by commas or new lines.
+#[derive(Debug)]
+pub struct Keywords {
+    patterns: Vec<Pattern>,
+}
+
+impl Keywords {
+    pub fn is_match(&self, text: &str) -> bool {
+        let lowercase_text = text.to_lowercase();
+
+        self.patterns.iter().any(|pattern| match pattern {
+            Pattern::Keyword(keyword) => lowercase_text.contains(keyword),
+            Pattern::Regex(regex) => regex.is_match(text),
+        })
+    }
+}
+
+impl FromStr for Keywords {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut patterns = vec![];
+        let mut rest = input.trim();
+
+        while !rest.is_empty() {
+            // regexes may contain commas, so they end at the closing slash
+            let (pattern, next) = match rest.strip_prefix('/') {
+                Some(regex) => {
+                    let (regex, next) = split_regex(regex)
+                        .ok_or(format!("{:?} is missing the closing slash", rest))?;
+
+                    let next = next.trim_start_matches([' ', '\t']);
+
+                    if !next.is_empty() && !next.starts_with([',', '\n']) {
+                        return Err(format!("{:?} has text after the closing slash", rest));
+                    }
+
+                    let regex = Regex::new(&regex)
+                        .map_err(|err| format!("{:?} is not a valid regex: {}", rest, err))?;
+
+                    (Some(Pattern::Regex(regex)), next)
+                }
+                None => {
+                    let end = rest.find([',', '\n']).unwrap_or(rest.len());
+                    let keyword = rest[..end].trim().to_lowercase();
+
+                    (
+                        (!keyword.is_empty()).then_some(Pattern::Keyword(keyword)),
+                        &rest[end..],
+                    )
+                }
+            };
+
+            patterns.extend(pattern);
+            rest = next.trim_start_matches([',', '\n', ' ']);
+        }
+
+        Ok(Self { patterns })
+    }
+}
+
+/// Splits a regex from the text following its closing slash. Escaped slashes
+/// (`\/`) are part of the regex.
+fn split_regex(input: &str) -> Option<(String, &str)> {
+    let mut regex = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((index, char)) = chars.next() {
+        match char {
+            '/' => return Some((regex, &input[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '/')) => regex.push('/'),
+                Some((_, char)) => {
+                    regex.push('\\');
+                    regex.push(char);
+                }
+                None => regex.push('\\'),
+            },
+            char => regex.push(char),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keywords;
+
+    #[test]
+    fn it_matches_keywords_and_regexes() {
+        let keywords = "rust, Web Assembly\n/\\bwasm(32)?\\b/, /^Release v\\d+,\\d+/"
+            .parse::<Keywords>()
+            .unwrap();
+
+        assert!(keywords.is_match("Why RUST is great"));
+        assert!(keywords.is_match("Trying web assembly"));
+        assert!(keywords.is_match("Targeting wasm32"));
+        assert!(keywords.is_match("Release v1,2 is out"));
+        assert!(!keywords.is_match("Targeting wasmtime"));
+        assert!(!keywords.is_match("New release v1,2"));
+        assert!(!keywords.is_match("Go generics"));
+    }
+
+    #[test]
+    fn it_matches_regexes_with_escaped_slashes() {
+        let keywords = r"/a\/b/, /\\/, rust".parse::<Keywords>().unwrap();
+
+        assert!(keywords.is_match("from a/b"));
+        assert!(keywords.is_match(r"C:\Windows"));
+        assert!(keywords.is_match("rust"));
+        assert!(!keywords.is_match("from a b"));
+    }
+
+    #[test]
+    fn it_ignores_empty_keywords() {
+        let keywords = " , rust,, ".parse::<Keywords>().unwrap();
+
+        assert!(keywords.is_match("rust"));
+        assert!(!keywords.is_match(""));
+        assert!(!"".parse::<Keywords>().unwrap().is_match("rust"));
+    }
+
+    #[test]
+    fn it_rejects_invalid_regexes() {
+        assert!("/(unclosed/".parse::<Keywords>().is_err());
+        assert!("rust, /missing slash".parse::<Keywords>().is_err());
+        assert!("/foo/bar".parse::<Keywords>().is_err());
+    }
+}
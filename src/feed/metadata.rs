@@ -0,0 +1,122 @@
+use rss::{extension::Extension, Item};
+use std::collections::BTreeMap;
+
+use super::calendar::EventTime;
+
+/// Extra metadata of items is kept in their extensions, so that adapters of
+/// other sources produce the same items as RSS feeds. The comment count uses
+/// the `slash:comments` element of WordPress feeds.
+const COMMENT_COUNT: (&str, &str) = ("slash", "comments");
+const SCORE: (&str, &str) = ("notion-feed", "score");
+const EVENT_START: (&str, &str) = ("notion-feed", "start");
+const EVENT_END: (&str, &str) = ("notion-feed", "end");
+const LOCATION: (&str, &str) = ("notion-feed", "location");
+
+/// Number of comments of an item.
+pub fn comment_count(item: &Item) -> Option<u64> {
+    value(item, COMMENT_COUNT)?.trim().parse().ok()
+}
+
+/// Score of an item: Reddit score, Hacker News points or GitHub reactions.
+pub fn score(item: &Item) -> Option<i64> {
+    value(item, SCORE)?.trim().parse().ok()
+}
+
+/// Start and end of an event.
+pub fn event_date(item: &Item) -> Option<(EventTime, Option<EventTime>)> {
+    let start = value(item, EVENT_START)?.parse().ok()?;
+    let end = value(item, EVENT_END).and_then(|end| end.parse().ok());
+
+    Some((start, end))
+}
+
+/// Location of an event.
+pub fn location(item: &Item) -> Option<String> {
+    value(item, LOCATION).map(str::to_string)
+}
+
+pub fn set_comment_count(item: &mut Item, comment_count: u64) {
+    insert(
+        item,
+        COMMENT_COUNT,
+        Some(comment_count.to_string()),
+        BTreeMap::new(),
+    );
+}
+
+pub fn set_score(item: &mut Item, score: i64) {
+    insert(item, SCORE, Some(score.to_string()), BTreeMap::new());
+}
+
+pub fn set_event_date(item: &mut Item, start: EventTime, end: Option<EventTime>) {
+    insert(item, EVENT_START, Some(start.to_string()), BTreeMap::new());
+
+    if let Some(end) = end {
+        insert(item, EVENT_END, Some(end.to_string()), BTreeMap::new());
+    }
+}
+
+pub fn set_location(item: &mut Item, location: &str) {
+    insert(item, LOCATION, Some(location.to_string()), BTreeMap::new());
+}
+
+/// Sets the `media:thumbnail` of an item, used as the cover of its page.
+pub fn set_thumbnail(item: &mut Item, url: &str) {
+    let attrs = BTreeMap::from([("url".to_string(), url.to_string())]);
+    insert(item, ("media", "thumbnail"), None, attrs);
+}
+
+fn value<'a>(item: &'a Item, (prefix, name): (&str, &str)) -> Option<&'a str> {
+    item.extensions
+        .get(prefix)?
+        .get(name)?
+        .first()?
+        .value
+        .as_deref()
+}
+
+fn insert(
+    item: &mut Item,
+    (prefix, name): (&str, &str),
+    value: Option<String>,
+    attrs: BTreeMap<String, String>,
+) {
+    let extension = Extension {
+        name: format!("{}:{}", prefix, name),
+        value,
+        attrs,
+        children: BTreeMap::new(),
+    };
+
+    item.extensions
+        .entry(prefix.to_string())
+        .or_default()
+        .insert(name.to_string(), vec![extension]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rss::Channel;
+
+    #[test]
+    fn it_reads_comment_counts_of_feeds() {
+        let channel = Channel::read_from(
+            r#"<rss version="2.0" xmlns:slash="http://purl.org/rss/1.0/modules/slash/"><channel>
+                <title>Blog</title><link>https://bereghici.dev</link><description>Blog</description>
+                <item><title>Post</title><slash:comments>12</slash:comments></item>
+            </channel></rss>"#
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let mut item = channel.items[0].clone();
+        assert_eq!(comment_count(&item), Some(12));
+        assert_eq!(score(&item), None);
+
+        set_score(&mut item, -3);
+        set_comment_count(&mut item, 13);
+        assert_eq!(score(&item), Some(-3));
+        assert_eq!(comment_count(&item), Some(13));
+    }
+}
@@ -1,6 +1,26 @@
+mod atom;
+mod calendar;
+mod cover;
+mod date;
+mod email;
+mod entry;
 mod feed_item;
+mod github;
+mod hacker_news;
+mod html;
+mod keywords;
+mod metadata;
+mod readability;
+mod reddit;
+mod rules;
+mod scrape;
 mod source;
+mod summary;
+mod watch;
+mod youtube;
 
 pub mod feed;
+pub mod opml;
+pub mod records;
 
 pub use self::feed::Feed;
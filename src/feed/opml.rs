@@ -0,0 +1,512 @@
+use chrono::Utc;
+use quick_xml::{
+    encoding::Decoder,
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::{collections::HashMap, error::Error, fmt, fs};
+
+use super::{
+    source::{text_content, SourceKind},
+    Feed,
+};
+use crate::notion::{
+    database::DatabaseKind,
+    models::{Page, PropertyValue, RichText, SelectOption},
+    Client,
+};
+
+/// Separator of the names of nested folders in the `Category` of a source.
+const FOLDER_SEPARATOR: &str = " / ";
+
+/// A feed of an OPML file.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Outline {
+    pub title: String,
+    pub xml_url: String,
+    pub html_url: Option<String>,
+    /// Folders containing the feed, e.g. `Tech / Rust`
+    pub category: Option<String>,
+    pub enabled: bool,
+}
+
+impl Outline {
+    /// Reads a page of the Sources database. Only RSS and Atom feeds are read,
+    /// the links of other sources aren't feeds. The `htmlUrl` is the `Website`
+    /// property.
+    pub fn from_page(page: &Page) -> Option<Outline> {
+        let properties = page.properties.as_ref()?;
+        let xml_url = source_link(page)?;
+
+        let kind = match properties.get("Type") {
+            Some(PropertyValue::Select {
+                select: Some(option),
+            }) => option.name.parse::<SourceKind>().ok()?,
+            _ => SourceKind::Feed,
+        };
+
+        if kind != SourceKind::Feed {
+            return None;
+        }
+
+        let html_url = match properties.get("Website") {
+            Some(PropertyValue::Url { url: Some(url) }) => Some(url.to_string()),
+            _ => None,
+        };
+
+        let title = match properties.get("Title") {
+            Some(PropertyValue::Title { title }) => text_content(title),
+            _ => None,
+        };
+
+        Some(Self {
+            title: title.unwrap_or_else(|| xml_url.to_string()),
+            xml_url,
+            html_url,
+            category: match properties.get("Category") {
+                Some(PropertyValue::Select {
+                    select: Some(option),
+                }) => Some(option.name.to_string()),
+                _ => None,
+            },
+            enabled: matches!(
+                properties.get("Enabled"),
+                Some(PropertyValue::Checkbox { checkbox: true })
+            ),
+        })
+    }
+}
+
+/// Returns the `Link` of a page of the Sources database.
+fn source_link(page: &Page) -> Option<String> {
+    match page.properties.as_ref()?.get("Link") {
+        Some(PropertyValue::Url { url: Some(url) }) => Some(url.to_string()),
+        _ => None,
+    }
+}
+
+/// Folder of an OPML file, with its feeds and nested folders.
+#[derive(Debug, Default)]
+struct Folder<'a> {
+    name: String,
+    folders: Vec<Folder<'a>>,
+    outlines: Vec<&'a Outline>,
+}
+
+impl<'a> Folder<'a> {
+    fn insert(&mut self, path: &[&str], outline: &'a Outline) {
+        let Some((name, path)) = path.split_first() else {
+            self.outlines.push(outline);
+            return;
+        };
+
+        let index = match self.folders.iter().position(|folder| folder.name == *name) {
+            Some(index) => index,
+            None => {
+                self.folders.push(Folder {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.folders.len() - 1
+            }
+        };
+
+        self.folders[index].insert(path, outline);
+    }
+
+    fn write(&self, writer: &mut Writer<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+        for outline in self.outlines.iter() {
+            let mut element = BytesStart::new("outline");
+            element.push_attribute(("type", "rss"));
+            element.push_attribute(("text", outline.title.as_str()));
+            element.push_attribute(("title", outline.title.as_str()));
+            element.push_attribute(("xmlUrl", outline.xml_url.as_str()));
+
+            if let Some(html_url) = &outline.html_url {
+                element.push_attribute(("htmlUrl", html_url.as_str()));
+            }
+
+            element.push_attribute(("enabled", if outline.enabled { "true" } else { "false" }));
+            writer.write_event(Event::Empty(element))?;
+        }
+
+        for folder in self.folders.iter() {
+            let mut element = BytesStart::new("outline");
+            element.push_attribute(("text", folder.name.as_str()));
+            element.push_attribute(("title", folder.name.as_str()));
+
+            writer.write_event(Event::Start(element))?;
+            folder.write(writer)?;
+            writer.write_event(Event::End(BytesEnd::new("outline")))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes feeds as an OPML 2.0 file, in folders by category.
+pub fn write(title: &str, outlines: &[Outline]) -> Result<String, Box<dyn Error>> {
+    let mut root = Folder::default();
+
+    for outline in outlines {
+        let path = outline
+            .category
+            .as_deref()
+            .map(|category| {
+                category
+                    .split(FOLDER_SEPARATOR)
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new);
+
+        root.insert(&path, outline);
+    }
+
+    let mut writer = Writer::new_with_indent(vec![], b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let mut opml = BytesStart::new("opml");
+    opml.push_attribute(("version", "2.0"));
+    writer.write_event(Event::Start(opml))?;
+
+    writer.write_event(Event::Start(BytesStart::new("head")))?;
+    for (name, value) in [
+        ("title", title.to_string()),
+        ("dateCreated", Utc::now().to_rfc2822()),
+    ] {
+        writer.write_event(Event::Start(BytesStart::new(name)))?;
+        writer.write_event(Event::Text(BytesText::new(&value)))?;
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("head")))?;
+
+    writer.write_event(Event::Start(BytesStart::new("body")))?;
+    root.write(&mut writer)?;
+    writer.write_event(Event::End(BytesEnd::new("body")))?;
+    writer.write_event(Event::End(BytesEnd::new("opml")))?;
+
+    Ok(String::from_utf8(writer.into_inner())? + "\n")
+}
+
+/// Counts of the feeds of an OPML file added to the Sources database.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub added: usize,
+    /// Feeds whose link is already a source
+    pub existing: usize,
+    /// Errors of the feeds which couldn't be added
+    pub failed: Vec<String>,
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Imported {} sources ({} already present, {} failed)",
+            self.added,
+            self.existing,
+            self.failed.len()
+        )
+    }
+}
+
+/// Reads the feeds of an OPML file. Outlines without `xmlUrl` are folders,
+/// which can be nested.
+pub fn read(xml: &str) -> Result<Vec<Outline>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut outlines = vec![];
+    // one entry per open outline: the folder name, or none for feeds
+    let mut folders: Vec<Option<String>> = vec![];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) if element.name().as_ref() == b"outline" => {
+                let attributes = attributes(&element, reader.decoder())?;
+
+                match outline(&attributes, &folders) {
+                    Some(outline) => {
+                        outlines.push(outline);
+                        folders.push(None);
+                    }
+                    None => folders.push(title(&attributes)),
+                }
+            }
+            Event::Empty(element) if element.name().as_ref() == b"outline" => {
+                outlines.extend(outline(&attributes(&element, reader.decoder())?, &folders));
+            }
+            Event::End(element) if element.name().as_ref() == b"outline" => {
+                folders.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(outlines)
+}
+
+/// Returns the attributes of an element, by lowercase name: some files use
+/// `xmlurl` instead of `xmlUrl`.
+fn attributes(
+    element: &BytesStart,
+    decoder: Decoder,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    element
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute?;
+
+            Ok((
+                String::from_utf8_lossy(attribute.key.as_ref()).to_lowercase(),
+                attribute
+                    .decode_and_unescape_value(decoder)?
+                    .trim()
+                    .to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn title(attributes: &HashMap<String, String>) -> Option<String> {
+    ["title", "text"]
+        .into_iter()
+        .filter_map(|name| attributes.get(name))
+        .find(|title| !title.is_empty())
+        .cloned()
+}
+
+fn outline(attributes: &HashMap<String, String>, folders: &[Option<String>]) -> Option<Outline> {
+    let xml_url = attributes.get("xmlurl").filter(|url| !url.is_empty())?;
+    let category = folders.iter().flatten().cloned().collect::<Vec<String>>();
+
+    Some(Outline {
+        title: title(attributes).unwrap_or_else(|| xml_url.to_string()),
+        xml_url: xml_url.to_string(),
+        html_url: attributes
+            .get("htmlurl")
+            .filter(|url| !url.is_empty())
+            .cloned(),
+        category: (!category.is_empty()).then(|| category.join(FOLDER_SEPARATOR)),
+        enabled: attributes
+            .get("enabled")
+            .is_none_or(|enabled| enabled != "false"),
+    })
+}
+
+/// Adds the feeds of an OPML file to the Sources database. Feeds whose link is
+/// already a source are skipped. Folders become the `Category` select of their
+/// feeds, and the `htmlUrl` their `Website`, when the database has them.
+pub async fn import(client: &Client<'_>, path: &str) -> Result<Import, Box<dyn Error>> {
+    let outlines = read(&fs::read_to_string(path)?)?;
+
+    let schema = client.retrieve_database(DatabaseKind::Source).await?;
+    let has_category = schema.property_type("Category") == Some("select");
+    let has_website = schema.property_type("Website") == Some("url");
+
+    let mut links = Feed::new(client)
+        .get_pages(DatabaseKind::Source)
+        .await?
+        .iter()
+        .filter_map(source_link)
+        .collect::<Vec<String>>();
+
+    let mut import = Import::default();
+
+    for outline in outlines {
+        if links.contains(&outline.xml_url) {
+            import.existing += 1;
+            continue;
+        }
+
+        let mut properties = HashMap::from([
+            (
+                "Title".to_string(),
+                PropertyValue::Title {
+                    title: RichText::plain(&outline.title),
+                },
+            ),
+            (
+                "Link".to_string(),
+                PropertyValue::Url {
+                    url: Some(outline.xml_url.to_string()),
+                },
+            ),
+            (
+                "Enabled".to_string(),
+                PropertyValue::Checkbox {
+                    checkbox: outline.enabled,
+                },
+            ),
+        ]);
+
+        if let Some(category) = outline.category.as_deref().filter(|_| has_category) {
+            properties.insert(
+                "Category".to_string(),
+                PropertyValue::Select {
+                    select: Some(SelectOption::new(category)),
+                },
+            );
+        }
+
+        if let Some(html_url) = outline.html_url.as_ref().filter(|_| has_website) {
+            properties.insert(
+                "Website".to_string(),
+                PropertyValue::Url {
+                    url: Some(html_url.to_string()),
+                },
+            );
+        }
+
+        match client
+            .create_page(DatabaseKind::Source, properties, vec![], None, None)
+            .await
+        {
+            Ok(_) => {
+                import.added += 1;
+                links.push(outline.xml_url);
+            }
+            Err(err) => {
+                import
+                    .failed
+                    .push(format!("Failed to import {}: {}", outline.xml_url, err));
+            }
+        }
+    }
+
+    Ok(import)
+}
+
+/// Writes the feeds of the Sources database, enabled or not, to an OPML file.
+/// Returns the number of exported feeds.
+pub async fn export(client: &Client<'_>, path: &str) -> Result<usize, Box<dyn Error>> {
+    let outlines = Feed::new(client)
+        .get_pages(DatabaseKind::Source)
+        .await?
+        .iter()
+        .filter_map(Outline::from_page)
+        .collect::<Vec<Outline>>();
+
+    fs::write(path, write("Notion Feed sources", &outlines)?)?;
+
+    Ok(outlines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_nested_folders() {
+        let outlines = read(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <opml version="2.0">
+                <head><title>Subscriptions</title></head>
+                <body>
+                    <outline text="Blogs" type="rss" xmlUrl="https://bereghici.dev/rss.xml" htmlUrl="https://bereghici.dev"/>
+                    <outline text="Tech" title="Tech">
+                        <outline text="Rust">
+                            <outline text="This Week in Rust" xmlUrl="https://this-week-in-rust.org/rss.xml"/>
+                            <outline title="Inside &amp; Rust" xmlurl="https://blog.rust-lang.org/inside-rust/feed.xml" enabled="false"></outline>
+                        </outline>
+                        <outline text="Hacker News" xmlUrl="https://news.ycombinator.com/rss"/>
+                    </outline>
+                    <outline text="Empty folder"></outline>
+                </body>
+            </opml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(outlines.len(), 4);
+        assert_eq!(
+            outlines[0],
+            Outline {
+                title: "Blogs".to_string(),
+                xml_url: "https://bereghici.dev/rss.xml".to_string(),
+                html_url: Some("https://bereghici.dev".to_string()),
+                category: None,
+                enabled: true,
+            }
+        );
+        assert_eq!(outlines[1].category.as_deref(), Some("Tech / Rust"));
+        assert_eq!(outlines[2].title, "Inside & Rust");
+        assert!(!outlines[2].enabled);
+        assert_eq!(outlines[3].category.as_deref(), Some("Tech"));
+    }
+
+    #[test]
+    fn it_writes_folders() {
+        let outline = |title: &str, category: Option<&str>, enabled: bool| Outline {
+            title: title.to_string(),
+            xml_url: format!("https://bereghici.dev/{}.xml", title.to_lowercase()),
+            html_url: None,
+            category: category.map(str::to_string),
+            enabled,
+        };
+
+        let outlines = vec![
+            outline("Rust", Some("Tech / Rust"), true),
+            outline("Pipelines", Some("Tech / CI/CD"), true),
+            outline("News", None, true),
+            outline("Go", Some("Tech"), false),
+            Outline {
+                html_url: Some("https://bereghici.dev/?a=1&b=2".to_string()),
+                ..outline("Wasm", Some("Tech / Rust"), true)
+            },
+        ];
+
+        let xml = write("Sources", &outlines).unwrap();
+
+        assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(xml.contains("<title>Sources</title>"));
+        assert!(xml.contains(
+            r#"<outline type="rss" text="Go" title="Go" xmlUrl="https://bereghici.dev/go.xml" enabled="false"/>"#
+        ));
+        assert!(xml.contains(r#"htmlUrl="https://bereghici.dev/?a=1&amp;b=2""#));
+        assert!(xml.contains(r#"<outline text="CI/CD" title="CI/CD">"#));
+
+        // the written file is read back the same
+        let mut read_outlines = read(&xml).unwrap();
+        read_outlines.sort_by(|a, b| a.title.cmp(&b.title));
+        let mut outlines = outlines;
+        outlines.sort_by(|a, b| a.title.cmp(&b.title));
+        assert_eq!(read_outlines, outlines);
+    }
+
+    #[test]
+    fn it_exports_only_feeds() {
+        let page = |kind: Option<&str>| Page {
+            id: "page".to_string(),
+            archived: false,
+            parent: None,
+            properties: Some(HashMap::from([
+                (
+                    "Link".to_string(),
+                    PropertyValue::Url {
+                        url: Some("https://www.reddit.com/r/rust".to_string()),
+                    },
+                ),
+                (
+                    "Type".to_string(),
+                    PropertyValue::Select {
+                        select: kind.map(SelectOption::new),
+                    },
+                ),
+            ])),
+        };
+
+        assert!(Outline::from_page(&page(None)).is_some());
+        assert!(Outline::from_page(&page(Some("Atom"))).is_some());
+        assert!(Outline::from_page(&page(Some("Reddit"))).is_none());
+        assert!(Outline::from_page(&page(Some("Watch"))).is_none());
+        assert!(Outline::from_page(&page(Some("Unknown"))).is_none());
+    }
+
+    #[test]
+    fn it_rejects_invalid_files() {
+        assert!(read("<opml><body><outline text=\"Tech\"></body></opml>").is_err());
+    }
+}
@@ -0,0 +1,323 @@
+use regex::Regex;
+use reqwest::Url;
+use scraper::{node::Node, ElementRef, Html, Selector};
+use std::{collections::HashMap, sync::LazyLock};
+
+use super::html::escape;
+
+/// Elements which are never part of an article.
+const BOILERPLATE_TAGS: [&str; 11] = [
+    "nav", "aside", "footer", "header", "form", "script", "style", "noscript", "iframe", "button",
+    "svg",
+];
+
+/// Elements kept in the extracted content, the others are replaced by their
+/// children.
+const CONTENT_TAGS: [&str; 32] = [
+    "p",
+    "div",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "blockquote",
+    "pre",
+    "code",
+    "b",
+    "strong",
+    "i",
+    "em",
+    "s",
+    "del",
+    "u",
+    "a",
+    "img",
+    "br",
+    "hr",
+    "figure",
+    "figcaption",
+    "table",
+    "tbody",
+    "tr",
+    "td",
+    "th",
+];
+
+/// Extracts the main content of an HTML page, like the reader mode of browsers:
+/// the element containing most of the text is kept, without navigation, ads
+/// and other boilerplate. Relative links and images are resolved against `base`.
+pub fn extract(html: &str, base: &Url) -> Option<String> {
+    let document = Html::parse_document(html);
+    let body = document.select(&Selector::parse("body").unwrap()).next()?;
+
+    let mut scores = HashMap::new();
+
+    for paragraph in body
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|element| matches!(element.value().name(), "p" | "pre" | "td"))
+        .filter(|element| !is_within_boilerplate(*element))
+    {
+        let text = paragraph.text().collect::<String>();
+        let length = text.trim().chars().count();
+
+        if length < 25 {
+            continue;
+        }
+
+        // longer paragraphs with more commas are more likely to be prose
+        let score = 1.0 + text.matches(',').count() as f64 + (length as f64 / 100.0).min(3.0);
+
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent.and_then(|parent| parent.parent().and_then(ElementRef::wrap));
+
+        for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            if let Some(ancestor) = ancestor {
+                *scores
+                    .entry(ancestor.id())
+                    .or_insert_with(|| initial_score(ancestor)) += score * share;
+            }
+        }
+    }
+
+    let candidate = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = document.tree.get(id).and_then(ElementRef::wrap)?;
+            Some((element, score * (1.0 - link_density(element))))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(element, _)| element)?;
+
+    let mut content = String::new();
+    write_content(candidate, base, &mut content);
+
+    Some(content.trim().to_string()).filter(|content| !content.is_empty())
+}
+
+/// Class names and ids of elements likely to contain the article.
+static POSITIVE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story")
+        .unwrap()
+});
+/// Class names and ids of elements unlikely to contain the article.
+static NEGATIVE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)hidden|banner|combx|comment|com-|contact|foot|footer|footnote|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget|ad-|advert").unwrap()
+});
+/// Class names and ids of boilerplate elements, unless they match `MAYBE_RE`.
+static UNLIKELY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)-ad-|ai2html|banner|breadcrumbs|combx|comment|community|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|related|remark|replies|rss|shoutbox|sidebar|skyscraper|social|sponsor|supplemental|ad-break|agegate|pagination|pager|popup|yom-remote|newsletter|cookie|advert").unwrap()
+});
+static MAYBE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)and|article|body|column|content|main|shadow").unwrap());
+
+fn class_and_id(element: ElementRef) -> String {
+    let value = element.value();
+
+    format!(
+        "{} {}",
+        value.attr("class").unwrap_or_default(),
+        value.attr("id").unwrap_or_default()
+    )
+}
+
+fn is_boilerplate(element: ElementRef) -> bool {
+    let name = element.value().name();
+
+    if BOILERPLATE_TAGS.contains(&name) {
+        return true;
+    }
+
+    if matches!(name, "body" | "article" | "main") {
+        return false;
+    }
+
+    let class_and_id = class_and_id(element);
+
+    UNLIKELY_RE.is_match(&class_and_id) && !MAYBE_RE.is_match(&class_and_id)
+}
+
+fn is_within_boilerplate(element: ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| is_boilerplate(ancestor))
+}
+
+fn initial_score(element: ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "article" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    let class_and_id = class_and_id(element);
+    let mut class_score = 0.0;
+
+    if POSITIVE_RE.is_match(&class_and_id) {
+        class_score += 25.0;
+    }
+    if NEGATIVE_RE.is_match(&class_and_id) {
+        class_score -= 25.0;
+    }
+
+    tag_score + class_score
+}
+
+/// Writes the content of the element as simplified HTML.
+fn write_content(element: ElementRef, base: &Url, content: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => content.push_str(&escape(text)),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    write_element(child, base, content);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_element(element: ElementRef, base: &Url, content: &mut String) {
+    let name = element.value().name();
+
+    // lists and blocks made of links are usually navigation
+    let is_link_list = matches!(name, "ul" | "ol" | "div" | "table") && link_density(element) > 0.5;
+
+    if is_boilerplate(element) || is_link_list {
+        return;
+    }
+
+    if !CONTENT_TAGS.contains(&name) {
+        return write_content(element, base, content);
+    }
+
+    let resolve = |attr: &str| {
+        element
+            .value()
+            .attr(attr)
+            .and_then(|url| base.join(url).ok())
+            .map(|url| format!(" {}=\"{}\"", attr, escape(url.as_str())))
+            .unwrap_or_default()
+    };
+
+    match name {
+        "img" => {
+            // lazy loaded images keep their URL in `data-src`
+            let src = element
+                .value()
+                .attr("data-src")
+                .or(element.value().attr("src"))
+                .and_then(|src| base.join(src).ok());
+
+            if let Some(src) = src {
+                content.push_str(&format!("<img src=\"{}\">", escape(src.as_str())));
+            }
+        }
+        "br" | "hr" => content.push_str(&format!("<{}>", name)),
+        _ => {
+            let attrs = match name {
+                "a" => resolve("href"),
+                _ => String::new(),
+            };
+
+            content.push_str(&format!("<{}{}>", name, attrs));
+            write_content(element, base, content);
+            content.push_str(&format!("</{}>", name));
+        }
+    }
+}
+
+/// Part of the text of the element inside links.
+fn link_density(element: ElementRef) -> f64 {
+    let length = element.text().map(|text| text.trim().len()).sum::<usize>();
+
+    if length == 0 {
+        return 0.0;
+    }
+
+    let link_length = element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|descendant| descendant.value().name() == "a")
+        .flat_map(|link| link.text())
+        .map(|text| text.trim().len())
+        .sum::<usize>();
+
+    link_length as f64 / length as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"
+        <html>
+        <head><title>Release</title><script>track()</script></head>
+        <body>
+            <header><nav><a href="/">Home</a> <a href="/blog">Blog</a></nav></header>
+            <div class="sidebar">
+                <p>Subscribe to our newsletter, it's great, really, you will love it.</p>
+            </div>
+            <div class="post-content">
+                <h1>Announcing the new release</h1>
+                <p>The team is happy to announce a new version, which brings faster builds,
+                   better error messages, and a lot of small improvements.</p>
+                <img data-src="/images/chart.png" src="data:image/gif;base64,R0lGOD">
+                <p>You can update with the usual command, as described in
+                   <a href="/docs/update">the documentation</a>, or from scratch.</p>
+                <div class="share-widget"><a href="https://twitter.com">Tweet</a></div>
+                <ul><li><a href="/a">Related post</a></li><li><a href="/b">Another one</a></li></ul>
+                <p>Thanks to everyone who contributed, 1 &lt; 2 &amp; all that, to this release!</p>
+            </div>
+            <footer><p>Copyright, all rights reserved, by the team since a long time.</p></footer>
+        </body>
+        </html>
+    "#;
+
+    #[test]
+    fn it_extracts_the_article() {
+        let base = Url::parse("https://bereghici.dev/blog/release").unwrap();
+        let content = extract(ARTICLE, &base).unwrap();
+
+        assert!(content.starts_with("<h1>Announcing the new release</h1>"));
+        assert!(content.contains("better error messages"));
+        assert!(content.contains(r#"<img src="https://bereghici.dev/images/chart.png">"#));
+        assert!(content
+            .contains(r#"<a href="https://bereghici.dev/docs/update">the documentation</a>"#));
+        assert!(content.contains("1 &lt; 2 &amp; all that"));
+
+        for boilerplate in ["Home", "newsletter", "Tweet", "Related post", "Copyright"] {
+            assert!(!content.contains(boilerplate), "{}", boilerplate);
+        }
+    }
+
+    #[test]
+    fn it_finds_nothing_without_text() {
+        let base = Url::parse("https://bereghici.dev").unwrap();
+
+        assert_eq!(
+            extract("<html><body><nav>Menu</nav></body></html>", &base),
+            None
+        );
+    }
+
+    #[test]
+    fn it_finds_nothing_without_usable_article() {
+        let base = Url::parse("https://bereghici.dev").unwrap();
+        let html = r#"<html><body><div>
+            <p class="newsletter">Subscribe to our newsletter, every week, for free.</p>
+        </div></body></html>"#;
+
+        assert_eq!(extract(html, &base), None);
+    }
+}
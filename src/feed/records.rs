@@ -0,0 +1,722 @@
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde_json::{Map, Number, Value};
+use std::{collections::HashMap, error::Error, fmt, fs, path::Path};
+
+use super::{date::parse_date, source::plain_text, Feed};
+use crate::notion::{
+    database::{Database, DatabaseKind},
+    models::{Date, Page, PropertyValue, Relation, RichText, SelectOption},
+    Client,
+};
+
+/// Format of a file of records, from its extension.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn new(path: &str) -> Result<Format, String> {
+        match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Ok(Format::Csv),
+            Some("json") => Ok(Format::Json),
+            _ => Err(format!("{:?} is not a .csv or .json file", path)),
+        }
+    }
+}
+
+/// Column of the page IDs, with which imported records update their page.
+const ID_COLUMN: &str = "id";
+
+/// Columns of a file and the properties they hold, e.g. `Name=Title, URL=Link`.
+/// Without mapping, the columns are named after the properties.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Mapping(Vec<(String, String)>);
+
+impl Mapping {
+    pub fn new(mapping: &str) -> Result<Mapping, String> {
+        mapping
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                let (column, property) = pair.split_once('=').ok_or(format!(
+                    "Invalid mapping: {:?}, expected Column=Property",
+                    pair
+                ))?;
+
+                Ok((column.trim().to_string(), property.trim().to_string()))
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()
+            .map(Mapping)
+    }
+
+    fn property(&self, column: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, property)| property.as_str())
+    }
+}
+
+/// Counts of the records of a file added to a database, or updating its pages.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub added: usize,
+    pub updated: usize,
+    /// Errors of the records which couldn't be added
+    pub failed: Vec<String>,
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Imported {} pages, updated {} ({} failed)",
+            self.added,
+            self.updated,
+            self.failed.len()
+        )
+    }
+}
+
+/// Returns the database of the `export` and `import` commands: `sources`,
+/// `feed`, or any other database by ID.
+pub fn database(name: &str) -> DatabaseKind {
+    match name.trim().to_lowercase().as_str() {
+        "sources" | "source" => DatabaseKind::Source,
+        "feed" => DatabaseKind::Feed,
+        _ => DatabaseKind::Id(name.trim().to_string()),
+    }
+}
+
+/// Returns the columns of the exported properties and their names: the mapped
+/// ones, or else all the properties, the title first.
+fn columns(schema: &Database, mapping: Option<&Mapping>) -> Result<Vec<(String, String)>, String> {
+    match mapping {
+        Some(Mapping(pairs)) => {
+            for (column, property) in pairs.iter() {
+                if schema.property_type(property).is_none() {
+                    return Err(format!(
+                        "Unknown property {:?} of column {:?}",
+                        property, column
+                    ));
+                }
+            }
+
+            Ok(pairs.clone())
+        }
+        None => {
+            let mut properties = schema
+                .properties
+                .iter()
+                .map(|(name, property)| (property.property_type != "title", name))
+                .collect::<Vec<(bool, &String)>>();
+            properties.sort();
+
+            Ok(properties
+                .into_iter()
+                .map(|(_, name)| (name.to_string(), name.to_string()))
+                .collect())
+        }
+    }
+}
+
+/// Converts a property to a JSON value: text as strings, multi-selects and
+/// relations as arrays, and dates as RFC 3339 strings, or days without time
+/// (`start/end` for ranges).
+fn value(property: &PropertyValue) -> Value {
+    let text = |rich_text: &[RichText]| Value::String(plain_text(rich_text));
+
+    match property {
+        PropertyValue::Title { title } => text(title),
+        PropertyValue::Text { rich_text } => text(rich_text),
+        PropertyValue::Url { url } => url.clone().map_or(Value::Null, Value::String),
+        PropertyValue::Checkbox { checkbox } => Value::Bool(*checkbox),
+        PropertyValue::Number { number } => number.clone().map_or(Value::Null, Value::Number),
+        PropertyValue::Select { select } => select
+            .as_ref()
+            .map_or(Value::Null, |option| Value::String(option.name.to_string())),
+        PropertyValue::MultiSelect { multi_select } => Value::Array(
+            multi_select
+                .iter()
+                .map(|option| Value::String(option.name.to_string()))
+                .collect(),
+        ),
+        PropertyValue::Date {
+            date:
+                Some(Date {
+                    start: Some(start),
+                    end,
+                    all_day,
+                    ..
+                }),
+        } => {
+            let format = |date: &DateTime<FixedOffset>| match all_day {
+                true => date.format("%Y-%m-%d").to_string(),
+                false => date.to_rfc3339(),
+            };
+
+            Value::String(match end {
+                Some(end) => format!("{}/{}", format(start), format(end)),
+                None => format(start),
+            })
+        }
+        PropertyValue::CreatedTime { created_time } => Value::String(created_time.to_rfc3339()),
+        PropertyValue::Relation { relation } => Value::Array(
+            relation
+                .iter()
+                .map(|relation| Value::String(relation.id.to_string()))
+                .collect(),
+        ),
+        PropertyValue::Date { .. } | PropertyValue::Formula { .. } | PropertyValue::Unsupported => {
+            Value::Null
+        }
+    }
+}
+
+/// Converts a value of a file to a property of the given type. Empty values and
+/// read-only properties are left out. Texts are kept as they are, the other
+/// values are trimmed.
+fn property(property_type: &str, value: &Value) -> Result<Option<PropertyValue>, String> {
+    let items = match value {
+        Value::Null => return Ok(None),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::String(text) => text.to_string(),
+                value => value.to_string(),
+            })
+            .collect::<Vec<String>>(),
+        Value::String(text) => vec![text.to_string()],
+        value => vec![value.to_string()],
+    };
+
+    let content = items.join(", ");
+    let text = content.trim().to_string();
+
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    // lists of a CSV file are a single value
+    let list = || match value {
+        Value::Array(_) => items
+            .iter()
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        _ => split_list(&text),
+    };
+
+    let property = match property_type {
+        "title" => PropertyValue::Title {
+            title: RichText::plain(&content),
+        },
+        "rich_text" => PropertyValue::Text {
+            rich_text: RichText::plain(&content),
+        },
+        "url" => PropertyValue::Url { url: Some(text) },
+        "checkbox" => PropertyValue::Checkbox {
+            checkbox: match text.to_lowercase().as_str() {
+                "true" | "yes" | "1" | "x" => true,
+                "false" | "no" | "0" => false,
+                _ => return Err(format!("{:?} is not a checkbox value", text)),
+            },
+        },
+        "number" => PropertyValue::Number {
+            number: Some(
+                text.parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .ok_or(format!("{:?} is not a number", text))?,
+            ),
+        },
+        "select" => PropertyValue::Select {
+            select: Some(SelectOption::new(&text)),
+        },
+        "multi_select" => PropertyValue::MultiSelect {
+            multi_select: list().iter().map(|name| SelectOption::new(name)).collect(),
+        },
+        "date" => PropertyValue::Date {
+            date: Some(date_range(&text).ok_or(format!("{:?} is not a date", text))?),
+        },
+        "relation" => PropertyValue::Relation {
+            relation: list().into_iter().map(|id| Relation { id }).collect(),
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(property))
+}
+
+/// Joins the items of a list, like the options of a multi-select, by commas.
+/// Items with commas or quotes, or spaces around them, are quoted like CSV
+/// fields, e.g. `rust, "a, b"`.
+fn join_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| {
+            let needs_quotes = item.contains([',', '"']) || item.trim() != item || item.is_empty();
+
+            match needs_quotes {
+                true => format!("\"{}\"", item.replace('"', "\"\"")),
+                false => item.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Splits a list joined by `join_list`. Unquoted items are trimmed, and empty
+/// ones left out.
+fn split_list(text: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut item = String::new();
+    // whether the item is quoted, and whether its closing quote was read
+    let mut quoted = false;
+    let mut closed = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && !closed => match chars.next_if_eq(&'"') {
+                Some(_) => item.push('"'),
+                None => closed = true,
+            },
+            ',' if !quoted || closed => {
+                items.push((std::mem::take(&mut item), quoted));
+                quoted = false;
+                closed = false;
+            }
+            '"' if !quoted && item.trim().is_empty() => {
+                item.clear();
+                quoted = true;
+            }
+            _ if closed => {}
+            char => item.push(char),
+        }
+    }
+
+    items.push((item, quoted));
+
+    items
+        .into_iter()
+        .map(|(item, quoted)| match quoted {
+            true => item,
+            false => item.trim().to_string(),
+        })
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Reads a date, or a `start/end` range of dates. Dates may contain slashes
+/// themselves, e.g. `2024/09/05`, so ranges are split where both sides are
+/// dates. Days without time are kept as days.
+fn date_range(text: &str) -> Option<Date> {
+    let day = |date: &str| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok();
+
+    if let Some(start) = day(text) {
+        return Some(Date::days(start, None));
+    }
+
+    if let Some(start) = parse_date(text) {
+        return Some(Date::new(start, None));
+    }
+
+    text.match_indices('/').find_map(|(index, _)| {
+        let (start, end) = (&text[..index], &text[index + 1..]);
+
+        match (day(start), day(end)) {
+            (Some(start), Some(end)) => Some(Date::days(start, Some(end))),
+            _ => Some(Date::range(
+                parse_date(start)?,
+                Some(parse_date(end)?),
+                None,
+            )),
+        }
+    })
+}
+
+/// Writes records as a CSV file, where lists are joined by `join_list`, or as a
+/// JSON array of objects.
+fn write(
+    format: Format,
+    columns: &[String],
+    records: &[Vec<Value>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            writer.write_record(columns)?;
+
+            for record in records {
+                writer.write_record(record.iter().map(|value| {
+                    match value {
+                        Value::Null => String::new(),
+                        Value::String(text) => text.to_string(),
+                        Value::Array(values) => join_list(
+                            &values
+                                .iter()
+                                .map(|value| {
+                                    value.as_str().map_or(value.to_string(), str::to_string)
+                                })
+                                .collect::<Vec<String>>(),
+                        ),
+                        value => value.to_string(),
+                    }
+                }))?;
+            }
+
+            Ok(writer.into_inner()?)
+        }
+        Format::Json => {
+            let records = records
+                .iter()
+                .map(|record| {
+                    Value::Object(
+                        columns
+                            .iter()
+                            .cloned()
+                            .zip(record.iter().cloned())
+                            .collect::<Map<String, Value>>(),
+                    )
+                })
+                .collect::<Vec<Value>>();
+
+            Ok(serde_json::to_vec_pretty(&records)?)
+        }
+    }
+}
+
+/// Reads the records of a CSV file with headers, or of a JSON array of objects.
+fn read(format: Format, content: &[u8]) -> Result<Vec<Map<String, Value>>, Box<dyn Error>> {
+    match format {
+        Format::Csv => {
+            let mut reader = csv::Reader::from_reader(content);
+            let columns = reader.headers()?.clone();
+
+            reader
+                .records()
+                .map(|record| {
+                    Ok(columns
+                        .iter()
+                        .zip(record?.iter())
+                        .map(|(column, value)| {
+                            (column.to_string(), Value::String(value.to_string()))
+                        })
+                        .collect())
+                })
+                .collect()
+        }
+        Format::Json => Ok(serde_json::from_slice(content)?),
+    }
+}
+
+/// Writes the pages of a database to a CSV or JSON file, one record per page,
+/// starting with the page ID. Returns the number of exported pages.
+pub async fn export(
+    client: &Client<'_>,
+    kind: DatabaseKind,
+    path: &str,
+    mapping: Option<&Mapping>,
+) -> Result<usize, Box<dyn Error>> {
+    let format = Format::new(path)?;
+    let schema = client.retrieve_database(kind.clone()).await?;
+    let columns = columns(&schema, mapping)?;
+    let pages = Feed::new(client).get_pages(kind).await?;
+
+    let records = pages
+        .iter()
+        .map(|page| record(page, &columns))
+        .collect::<Vec<Vec<Value>>>();
+
+    let names = std::iter::once(ID_COLUMN.to_string())
+        .chain(columns.into_iter().map(|(column, _)| column))
+        .collect::<Vec<String>>();
+
+    fs::write(path, write(format, &names, &records)?)?;
+
+    Ok(records.len())
+}
+
+fn record(page: &Page, columns: &[(String, String)]) -> Vec<Value> {
+    std::iter::once(Value::String(page.id.to_string()))
+        .chain(columns.iter().map(|(_, name)| {
+            page.properties
+                .as_ref()
+                .and_then(|properties| properties.get(name))
+                .map_or(Value::Null, value)
+        }))
+        .collect()
+}
+
+/// Returns the page ID of a record, if it has one.
+fn record_id(record: &Map<String, Value>) -> Option<&str> {
+    record
+        .get(ID_COLUMN)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+}
+
+/// Page IDs without dashes, which spreadsheets may remove.
+fn same_id(a: &str, b: &str) -> bool {
+    a.replace('-', "").eq_ignore_ascii_case(&b.replace('-', ""))
+}
+
+/// Creates a page of a database for each record of a CSV or JSON file, or
+/// updates the page of the records with the ID of a page of the database, e.g.
+/// exported and edited in a spreadsheet. The values are converted to the type
+/// of their property, and columns which aren't properties (or mapped) are
+/// ignored.
+pub async fn import(
+    client: &Client<'_>,
+    kind: DatabaseKind,
+    path: &str,
+    mapping: Option<&Mapping>,
+) -> Result<Import, Box<dyn Error>> {
+    let format = Format::new(path)?;
+    let records = read(format, &fs::read(path)?)?;
+    let schema = client.retrieve_database(kind.clone()).await?;
+    // IDs of other databases, e.g. of another workspace, create new pages
+    let pages = Feed::new(client).get_pages(kind.clone()).await?;
+
+    let mut import = Import::default();
+
+    for (index, record) in records.iter().enumerate() {
+        let properties = match properties(&schema, mapping, record) {
+            Ok(properties) => properties,
+            Err(err) => {
+                import
+                    .failed
+                    .push(format!("Failed to import record {}: {}", index + 1, err));
+                continue;
+            }
+        };
+
+        let page = record_id(record).and_then(|id| pages.iter().find(|page| same_id(&page.id, id)));
+
+        let result = match page {
+            Some(page) => client
+                .update_page(&page.id, properties)
+                .await
+                .map(|_| import.updated += 1),
+            None => client
+                .create_page(kind.clone(), properties, vec![], None, None)
+                .await
+                .map(|_| import.added += 1),
+        };
+
+        match result {
+            Ok(_) => {}
+            Err(err) => {
+                import
+                    .failed
+                    .push(format!("Failed to import record {}: {}", index + 1, err))
+            }
+        }
+    }
+
+    Ok(import)
+}
+
+fn properties(
+    schema: &Database,
+    mapping: Option<&Mapping>,
+    record: &Map<String, Value>,
+) -> Result<HashMap<String, PropertyValue>, String> {
+    let mut properties = HashMap::new();
+
+    for (column, value) in record.iter().filter(|(column, _)| *column != ID_COLUMN) {
+        let name = match mapping {
+            Some(mapping) => match mapping.property(column) {
+                Some(name) => name,
+                None => continue,
+            },
+            None => column.as_str(),
+        };
+
+        let Some(property_type) = schema.property_type(name) else {
+            match mapping {
+                Some(_) => return Err(format!("Unknown property {:?}", name)),
+                None => continue,
+            }
+        };
+
+        if let Some(property) = property(property_type, value)
+            .map_err(|err| format!("Invalid {} of {:?}: {}", property_type, column, err))?
+        {
+            properties.insert(name.to_string(), property);
+        }
+    }
+
+    Ok(properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notion::database::PropertySchema;
+
+    fn schema() -> Database {
+        let property = |property_type: &str| PropertySchema {
+            id: property_type.to_string(),
+            property_type: property_type.to_string(),
+        };
+
+        Database {
+            id: "database".to_string(),
+            properties: HashMap::from([
+                ("Title".to_string(), property("title")),
+                ("Link".to_string(), property("url")),
+                ("Enabled".to_string(), property("checkbox")),
+                ("Tags".to_string(), property("multi_select")),
+                ("Max items".to_string(), property("number")),
+                ("Published At".to_string(), property("date")),
+                ("Event date".to_string(), property("date")),
+                ("Status".to_string(), property("status")),
+                ("Created".to_string(), property("created_time")),
+            ]),
+        }
+    }
+
+    #[test]
+    fn it_exports_and_imports_records() {
+        let page: Page = serde_json::from_str(
+            r#"{
+                "id": "page",
+                "archived": false,
+                "parent": null,
+                "properties": {
+                    "Title": {"type": "title", "title": [{"type": "text", "text": {"content": "Rust, Blog"}}]},
+                    "Link": {"type": "url", "url": "https://blog.rust-lang.org"},
+                    "Enabled": {"type": "checkbox", "checkbox": true},
+                    "Tags": {"type": "multi_select", "multi_select": [{"name": "rust"}, {"name": "news"}, {"name": "say \"hi\""}]},
+                    "Max items": {"type": "number", "number": null},
+                    "Published At": {"type": "date", "date": {"start": "2024-09-05T10:00:00+00:00", "end": "2024-09-06T12:00:00+00:00"}},
+                    "Event date": {"type": "date", "date": {"start": "2024-09-05", "end": "2024-09-06"}},
+                    "Status": {"type": "status", "status": {"name": "Done"}}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let columns = columns(&schema(), None).unwrap();
+        assert_eq!(columns[0].0, "Title");
+
+        let names = std::iter::once(ID_COLUMN.to_string())
+            .chain(columns.iter().map(|(column, _)| column.to_string()))
+            .collect::<Vec<String>>();
+        let records = vec![record(&page, &columns)];
+
+        for format in [Format::Csv, Format::Json] {
+            let content = write(format, &names, &records).unwrap();
+            let read = read(format, &content).unwrap();
+            let imported = properties(&schema(), None, &read[0]).unwrap();
+            assert_eq!(record_id(&read[0]), Some("page"));
+
+            for name in [
+                "Title",
+                "Link",
+                "Enabled",
+                "Tags",
+                "Published At",
+                "Event date",
+            ] {
+                assert_eq!(
+                    imported.get(name),
+                    page.properties.as_ref().unwrap().get(name),
+                    "{} of {:?}",
+                    name,
+                    format
+                );
+            }
+
+            assert!(!imported.contains_key("Max items"));
+            assert!(!imported.contains_key("Created"));
+            assert!(!imported.contains_key("Status"));
+        }
+
+        let csv = String::from_utf8(write(Format::Csv, &names, &records).unwrap()).unwrap();
+        assert!(csv.starts_with(
+            "id,Title,Created,Enabled,Event date,Link,Max items,Published At,Status,Tags\n"
+        ));
+        assert!(csv.contains(
+            r#"page,"Rust, Blog",,true,2024-09-05/2024-09-06,https://blog.rust-lang.org,,"#
+        ));
+        assert!(csv.ends_with(
+            r#",,"rust, news, ""say """"hi"""""""
+"#
+        ));
+    }
+
+    #[test]
+    fn it_splits_quoted_list_items() {
+        let items = ["rust", "news, weekly", "say \"hi\"", " padded"].map(str::to_string);
+
+        assert_eq!(split_list(&join_list(&items)), items);
+        assert_eq!(split_list(" rust ,, news "), ["rust", "news"]);
+        assert_eq!(split_list(r#""a, b" , c"#), ["a, b", "c"]);
+        assert!(same_id(
+            "0f1e2d3c-4b5a-6978-8796-a5b4c3d2e1f0",
+            "0F1E2D3C4B5A69788796A5B4C3D2E1F0"
+        ));
+    }
+
+    #[test]
+    fn it_reads_dates_with_slashes() {
+        let date = |text: &str| {
+            serde_json::to_value(date_range(text).unwrap())
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            date("2024/09/05"),
+            r#"{"end":null,"start":"2024-09-05T00:00:00+00:00"}"#
+        );
+        assert_eq!(
+            date("2024/09/05 10:00/2024/09/06 12:00"),
+            r#"{"end":"2024-09-06T12:00:00+00:00","start":"2024-09-05T10:00:00+00:00"}"#
+        );
+        assert_eq!(
+            date("2024-09-05/2024-09-06"),
+            r#"{"end":"2024-09-06","start":"2024-09-05"}"#
+        );
+        assert!(date_range("2024/13/05").is_none());
+    }
+
+    #[test]
+    fn it_maps_columns_to_properties() {
+        let mapping = Mapping::new("Name=Title, URL=Link, Active=Enabled").unwrap();
+        let records = read(
+            Format::Csv,
+            b"Name,URL,Active,Notes\nThis Week in Rust,https://this-week-in-rust.org/rss.xml,yes,weekly\n",
+        )
+        .unwrap();
+
+        let imported = properties(&schema(), Some(&mapping), &records[0]).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert_eq!(
+            imported.get("Enabled"),
+            Some(&PropertyValue::Checkbox { checkbox: true })
+        );
+
+        let records = read(Format::Json, br#"[{"Name": "Blog", "Active": "maybe"}]"#).unwrap();
+        assert!(properties(&schema(), Some(&mapping), &records[0])
+            .unwrap_err()
+            .contains("checkbox"));
+
+        let mapping = Mapping::new("Name=Name").unwrap();
+        assert!(columns(&schema(), Some(&mapping)).is_err());
+        assert!(Mapping::new("Name").is_err());
+        assert_eq!(Format::new("sources.CSV"), Ok(Format::Csv));
+        assert!(Format::new("sources.xlsx").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::DateTime;
+use reqwest::Url;
+use rss::{Category, Channel, Item};
+use serde::Deserialize;
+use std::error::Error;
+
+use super::metadata;
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListingData {
+    children: Vec<Thing>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Thing {
+    data: Post,
+}
+
+#[derive(Debug, Deserialize)]
+struct Post {
+    title: String,
+    author: String,
+    subreddit_name_prefixed: String,
+    permalink: String,
+    url: Option<String>,
+    created_utc: f64,
+    score: i64,
+    num_comments: u64,
+    is_self: bool,
+    #[serde(default)]
+    stickied: bool,
+    selftext_html: Option<String>,
+    link_flair_text: Option<String>,
+    preview: Option<Preview>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Preview {
+    images: Vec<PreviewImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PreviewImage {
+    source: PreviewSource,
+}
+
+#[derive(Debug, Deserialize)]
+struct PreviewSource {
+    url: String,
+}
+
+/// Returns the JSON listing of a subreddit page, e.g.
+/// `https://www.reddit.com/r/rust/top/?t=week`.
+pub fn api_url(link: &str) -> Result<String, String> {
+    let mut url = Url::parse(link).map_err(|err| format!("{:?} is not a URL: {}", link, err))?;
+
+    if !matches!(
+        url.host_str(),
+        Some("reddit.com" | "www.reddit.com" | "old.reddit.com")
+    ) {
+        return Err(format!("{:?} is not a Reddit URL", link));
+    }
+
+    let path = url.path().trim_end_matches('/').to_string();
+
+    if !path.ends_with(".json") {
+        url.set_path(&format!("{}.json", path));
+    }
+
+    url.set_host(Some("www.reddit.com"))
+        .map_err(|err| err.to_string())?;
+
+    Ok(url.to_string())
+}
+
+/// Reads the posts of a subreddit listing as items. Posts link to their
+/// article, self posts to their discussion. Pinned posts are left out.
+pub fn read_channel(content: &[u8]) -> Result<Channel, Box<dyn Error>> {
+    let listing = serde_json::from_slice::<Listing>(content)?;
+
+    let title = listing
+        .data
+        .children
+        .first()
+        .map(|thing| thing.data.subreddit_name_prefixed.to_string())
+        .unwrap_or_default();
+
+    let items = listing
+        .data
+        .children
+        .into_iter()
+        .map(|thing| thing.data)
+        .filter(|post| !post.stickied)
+        .map(|post| {
+            let comments = format!("https://www.reddit.com{}", post.permalink);
+
+            let mut item = Item {
+                title: Some(post.title),
+                link: post
+                    .url
+                    .filter(|_| !post.is_self)
+                    .or(Some(comments.clone())),
+                description: post.selftext_html.as_deref().map(unescape),
+                author: Some(format!("u/{}", post.author)),
+                categories: post
+                    .link_flair_text
+                    .into_iter()
+                    .map(|flair| Category {
+                        name: flair,
+                        domain: None,
+                    })
+                    .collect(),
+                comments: Some(comments),
+                pub_date: DateTime::from_timestamp(post.created_utc as i64, 0)
+                    .map(|date| date.to_rfc2822()),
+                ..Default::default()
+            };
+
+            metadata::set_score(&mut item, post.score);
+            metadata::set_comment_count(&mut item, post.num_comments);
+
+            if let Some(image) = post
+                .preview
+                .and_then(|preview| preview.images.into_iter().next())
+            {
+                metadata::set_thumbnail(&mut item, &unescape(&image.source.url));
+            }
+
+            item
+        })
+        .collect();
+
+    Ok(Channel {
+        link: format!("https://www.reddit.com/{}", title),
+        title,
+        items,
+        ..Default::default()
+    })
+}
+
+/// Reddit escapes the HTML of self posts and the URLs of previews.
+fn unescape(html: &str) -> String {
+    html.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::cover::item_image;
+
+    #[test]
+    fn it_reads_subreddit_posts() {
+        let channel = read_channel(include_bytes!("../../fixtures/reddit.json")).unwrap();
+
+        assert_eq!(channel.title, "r/rust");
+        assert_eq!(channel.link, "https://www.reddit.com/r/rust");
+        assert_eq!(channel.items.len(), 3);
+
+        let item = &channel.items[0];
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://www.reddit.com/r/rust/comments/1fnfxyk/whats_everyone_working_on_this_week_392024/")
+        );
+        assert_eq!(
+            item.description.as_deref(),
+            Some("<!-- SC_OFF --><div class=\"md\"><p>What are you working on this week?</p>\n</div><!-- SC_ON -->")
+        );
+
+        let item = &channel.items[1];
+        assert_eq!(item.title.as_deref(), Some("Announcing Rust 1.81.0"));
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://blog.rust-lang.org/2024/09/05/Rust-1.81.0.html")
+        );
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://www.reddit.com/r/rust/comments/1f9x0ke/announcing_rust_1810/")
+        );
+        assert_eq!(item.author.as_deref(), Some("u/burntsushi"));
+        assert_eq!(item.categories[0].name, "📢 announcement");
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Thu, 5 Sep 2024 15:13:23 +0000")
+        );
+        assert_eq!(metadata::score(item), Some(612));
+        assert_eq!(metadata::comment_count(item), Some(97));
+        assert_eq!(
+            item_image(item).as_deref(),
+            Some("https://external-preview.redd.it/rust.png?width=1200&format=png&s=2a5f")
+        );
+        assert_eq!(item_image(&channel.items[2]), None);
+    }
+
+    #[test]
+    fn it_finds_subreddit_listings() {
+        assert_eq!(
+            api_url("https://old.reddit.com/r/rust/").as_deref(),
+            Ok("https://www.reddit.com/r/rust.json")
+        );
+        assert_eq!(
+            api_url("https://www.reddit.com/r/rust/top/?t=week").as_deref(),
+            Ok("https://www.reddit.com/r/rust/top.json?t=week")
+        );
+        assert!(api_url("https://bereghici.dev/r/rust").is_err());
+    }
+}
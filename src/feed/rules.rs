@@ -0,0 +1,320 @@
+use chrono::{NaiveDate, Utc};
+use regex::Regex;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{error::Error, fs, path::Path};
+
+use crate::notion::database::DatabaseKind;
+
+use super::{
+    entry::Entry,
+    source::{Offset, Source},
+};
+
+/// Rules applied to every new item, loaded from a TOML or YAML file:
+///
+/// ```toml
+/// [[rules]]
+/// when = { title = "(?i)release", source = "Rust Blog" }
+/// then = { starred = true, tags = ["rust"], priority = "High" }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Rules {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    #[serde(default)]
+    when: Conditions,
+    then: Actions,
+}
+
+/// Regexes matched against the item, and its age. All of them must match.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Conditions {
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    title: Option<Regex>,
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    link: Option<Regex>,
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    author: Option<Regex>,
+    /// Matches when any of the item categories matches
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    category: Option<Regex>,
+    /// Matched against the title of the source page
+    #[serde(default, deserialize_with = "deserialize_regex")]
+    source: Option<Regex>,
+    #[serde(default, deserialize_with = "deserialize_offset")]
+    older_than: Option<Offset>,
+    #[serde(default, deserialize_with = "deserialize_offset")]
+    newer_than: Option<Offset>,
+}
+
+/// What happens to the items matching a rule. When several rules match, tags
+/// are combined and the last rule setting the other actions wins.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Actions {
+    /// Don't add the item to the feed
+    #[serde(default)]
+    pub skip: bool,
+    #[serde(default)]
+    pub starred: bool,
+    /// Options of the `Tags` multi-select property
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Option of the `Priority` select property
+    pub priority: Option<String>,
+    /// ID of the feed database where the item is added instead
+    pub database: Option<String>,
+}
+
+impl Actions {
+    /// Returns the feed database where the item is added.
+    pub fn feed_database(&self) -> DatabaseKind {
+        match &self.database {
+            Some(id) => DatabaseKind::Id(id.to_string()),
+            None => DatabaseKind::Feed,
+        }
+    }
+}
+
+impl Rules {
+    /// Loads the rules file. Without a path, there are no rules.
+    pub fn load(path: Option<&str>) -> Result<Rules, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(Rules::default()),
+        };
+
+        let content = fs::read_to_string(path)?;
+
+        let rules = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str::<Rules>(&content)?,
+            Some("toml") => toml::from_str::<Rules>(&content)?,
+            _ => {
+                return Err(format!("Unsupported rules file {:?}, use .toml or .yaml", path).into())
+            }
+        };
+
+        Ok(rules)
+    }
+
+    /// IDs of the databases where items can be routed.
+    pub fn databases(&self) -> Vec<String> {
+        let mut databases = self
+            .rules
+            .iter()
+            .filter_map(|rule| rule.then.database.clone())
+            .collect::<Vec<String>>();
+
+        databases.sort();
+        databases.dedup();
+        databases
+    }
+
+    pub fn apply(&self, entry: &Entry, source: &Source) -> Actions {
+        let today = Utc::now().date_naive();
+        let mut actions = Actions::default();
+
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.when.is_match(entry, source, today))
+        {
+            actions.skip |= rule.then.skip;
+            actions.starred |= rule.then.starred;
+
+            for tag in rule.then.tags.iter() {
+                if !actions.tags.contains(tag) {
+                    actions.tags.push(tag.to_string());
+                }
+            }
+
+            if rule.then.priority.is_some() {
+                actions.priority = rule.then.priority.clone();
+            }
+
+            if rule.then.database.is_some() {
+                actions.database = rule.then.database.clone();
+            }
+        }
+
+        actions
+    }
+}
+
+impl Conditions {
+    fn is_match(&self, entry: &Entry, source: &Source, today: NaiveDate) -> bool {
+        let matches = |regex: &Option<Regex>, text: Option<&str>| match regex {
+            Some(regex) => text.is_some_and(|text| regex.is_match(text)),
+            None => true,
+        };
+
+        let date = entry.created_date.date_naive();
+
+        matches(&self.title, Some(&entry.title))
+            && matches(&self.link, Some(&entry.link))
+            && matches(&self.author, entry.author.as_deref())
+            && matches(&self.source, source.name.as_deref())
+            && self.category.as_ref().is_none_or(|regex| {
+                entry
+                    .categories
+                    .iter()
+                    .any(|category| regex.is_match(category))
+            })
+            && self
+                .older_than
+                .and_then(|offset| offset.since(today))
+                .is_none_or(|since| date < since)
+            && self
+                .newer_than
+                .and_then(|offset| offset.since(today))
+                .is_none_or(|since| date >= since)
+    }
+}
+
+fn deserialize_regex<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Regex>, D::Error> {
+    let regex = String::deserialize(deserializer)?;
+
+    Regex::new(&regex).map(Some).map_err(D::Error::custom)
+}
+
+fn deserialize_offset<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Offset>, D::Error> {
+    let offset = String::deserialize(deserializer)?;
+
+    offset.parse::<Offset>().map(Some).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+
+    const TOML_RULES: &str = r#"
+        [[rules]]
+        when = { title = "(?i)release", source = "^Rust" }
+        then = { starred = true, tags = ["rust", "release"], priority = "High" }
+
+        [[rules]]
+        when = { category = "^sponsored$" }
+        then = { skip = true }
+
+        [[rules]]
+        when = { author = "Alexandru", older_than = "1 week" }
+        then = { tags = ["rust", "archive"], priority = "Low", database = "archive-db" }
+    "#;
+
+    const YAML_RULES: &str = r#"
+        rules:
+          - when:
+              link: github\.com
+              newer_than: 2 days
+            then:
+              tags: [github]
+    "#;
+
+    fn source(name: &str) -> Source {
+        Source {
+            page_id: "page".to_string(),
+            name: Some(name.to_string()),
+            link: "https://blog.rust-lang.org/feed.xml".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entry(title: &str, days_ago: u64) -> Entry {
+        let created_date = Utc::now()
+            .checked_sub_days(Days::new(days_ago))
+            .unwrap()
+            .fixed_offset();
+
+        Entry {
+            title: title.to_string(),
+            link: "https://github.com/rust-lang/rust/releases".to_string(),
+            pub_date: Some(created_date),
+            created_date,
+            author: Some("Alexandru Bereghici".to_string()),
+            categories: vec!["news".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_applies_matching_rules() {
+        let rules = toml::from_str::<Rules>(TOML_RULES).unwrap();
+
+        assert_eq!(
+            rules.apply(&entry("Rust 1.70 Release", 0), &source("Rust Blog")),
+            Actions {
+                starred: true,
+                tags: vec!["rust".to_string(), "release".to_string()],
+                priority: Some("High".to_string()),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            rules.apply(&entry("Rust 1.70 Release", 10), &source("Rust Blog")),
+            Actions {
+                starred: true,
+                tags: vec![
+                    "rust".to_string(),
+                    "release".to_string(),
+                    "archive".to_string()
+                ],
+                priority: Some("Low".to_string()),
+                database: Some("archive-db".to_string()),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            rules.apply(&entry("Rust 1.70 Release", 0), &source("This Week in Rust")),
+            Actions::default()
+        );
+
+        let mut sponsored = entry("Sponsored post", 0);
+        sponsored.categories.push("sponsored".to_string());
+        assert!(rules.apply(&sponsored, &source("Rust Blog")).skip);
+
+        assert_eq!(rules.databases(), vec!["archive-db".to_string()]);
+    }
+
+    #[test]
+    fn it_loads_yaml_rules() {
+        let rules = serde_yaml::from_str::<Rules>(YAML_RULES).unwrap();
+
+        assert_eq!(
+            rules.apply(&entry("New issue", 1), &source("GitHub")).tags,
+            vec!["github".to_string()]
+        );
+        assert!(rules
+            .apply(&entry("Old issue", 3), &source("GitHub"))
+            .tags
+            .is_empty());
+    }
+
+    #[test]
+    fn it_rejects_invalid_rules() {
+        assert!(
+            toml::from_str::<Rules>(r#"rules = [{ when = { title = "(" }, then = {} }]"#).is_err()
+        );
+        assert!(toml::from_str::<Rules>(
+            r#"rules = [{ when = { older_than = "soon" }, then = {} }]"#
+        )
+        .is_err());
+        assert!(
+            toml::from_str::<Rules>(r#"rules = [{ when = { color = "red" }, then = {} }]"#)
+                .is_err()
+        );
+        assert!(toml::from_str::<Rules>(r#"rules = [{ when = {} }]"#).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use reqwest::Url;
+use rss::{Channel, Item};
+use scraper::{ElementRef, Html, Selector};
+
+use super::{date::parse_date, metadata};
+
+/// CSS selectors of the items of a web page without feed, from the
+/// `Item selector`, `Title selector`, `Link selector` and `Date selector`
+/// properties of its source. The other selectors apply within each item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selectors {
+    pub item: Selector,
+    pub title: Option<Selector>,
+    pub link: Option<Selector>,
+    pub date: Option<Selector>,
+}
+
+impl Selectors {
+    pub fn new(
+        item: &str,
+        title: Option<&str>,
+        link: Option<&str>,
+        date: Option<&str>,
+    ) -> Result<Selectors, String> {
+        Ok(Self {
+            item: selector(item)?,
+            title: title.map(selector).transpose()?,
+            link: link.map(selector).transpose()?,
+            date: date.map(selector).transpose()?,
+        })
+    }
+}
+
+fn selector(selector: &str) -> Result<Selector, String> {
+    Selector::parse(selector)
+        .map_err(|err| format!("{:?} is not a CSS selector: {}", selector, err))
+}
+
+/// Reads the items of a web page. An item without title selector is titled by
+/// its text, and an item without link selector links to its first link (or
+/// itself, when it's a link). Items without title or link are left out.
+pub fn read_channel(html: &str, base: &Url, selectors: &Selectors) -> Channel {
+    let document = Html::parse_document(html);
+
+    let items = document
+        .select(&selectors.item)
+        .filter_map(|element| {
+            let title = match &selectors.title {
+                Some(selector) => element.select(selector).next().map(text),
+                None => Some(text(element)),
+            }
+            .filter(|title| !title.is_empty())?;
+
+            let link = match &selectors.link {
+                Some(selector) => element.select(selector).next(),
+                None => first(element, "a[href]"),
+            }
+            .and_then(|link| link.value().attr("href"))
+            .and_then(|href| base.join(href.trim()).ok())?;
+
+            let pub_date = selectors
+                .date
+                .as_ref()
+                .and_then(|selector| element.select(selector).next())
+                .and_then(|date| {
+                    // `<time datetime>` and `<meta content>` are more precise
+                    // than the displayed date
+                    ["datetime", "content"]
+                        .into_iter()
+                        .find_map(|attr| date.value().attr(attr).and_then(parse_date))
+                        .or_else(|| parse_date(&text(date)))
+                });
+
+            let mut item = Item {
+                title: Some(title),
+                link: Some(link.to_string()),
+                pub_date: pub_date.map(|date| date.to_rfc2822()),
+                ..Default::default()
+            };
+
+            if let Some(image) = first(element, "img[src]")
+                .and_then(|image| image.value().attr("src"))
+                .and_then(|src| base.join(src.trim()).ok())
+            {
+                metadata::set_thumbnail(&mut item, image.as_str());
+            }
+
+            Some(item)
+        })
+        .collect();
+
+    let title = first(document.root_element(), "title")
+        .map(text)
+        .unwrap_or_default();
+
+    Channel {
+        title,
+        link: base.to_string(),
+        items,
+        ..Default::default()
+    }
+}
+
+/// Returns the element itself or its first descendant matching the selector.
+fn first<'a>(element: ElementRef<'a>, selector: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(selector).unwrap();
+
+    match selector.matches(&element) {
+        true => Some(element),
+        false => element.select(&selector).next(),
+    }
+}
+
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::cover::item_image;
+
+    const PAGE: &str = r#"
+        <html>
+        <head><title>Changelog</title></head>
+        <body>
+            <nav><a href="/">Home</a></nav>
+            <article class="post">
+                <h2><a href="/changelog/2024-10">October release</a></h2>
+                <time datetime="2024-10-01T09:00:00Z">Oct 1</time>
+                <img src="/images/october.png">
+            </article>
+            <article class="post">
+                <h2>September release</h2>
+                <a class="more" href="https://bereghici.dev/changelog/2024-09">Read more</a>
+                <time>5 September 2024</time>
+            </article>
+            <article class="post"><h2>Draft</h2></article>
+        </body>
+        </html>
+    "#;
+
+    #[test]
+    fn it_scrapes_items() {
+        let base = Url::parse("https://bereghici.dev/changelog").unwrap();
+        let selectors = Selectors::new("article.post", Some("h2"), None, Some("time")).unwrap();
+        let channel = read_channel(PAGE, &base, &selectors);
+
+        assert_eq!(channel.title, "Changelog");
+        assert_eq!(channel.items.len(), 2);
+
+        let item = &channel.items[0];
+        assert_eq!(item.title.as_deref(), Some("October release"));
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://bereghici.dev/changelog/2024-10")
+        );
+        assert_eq!(
+            item.pub_date.as_deref(),
+            Some("Tue, 1 Oct 2024 09:00:00 +0000")
+        );
+        assert_eq!(
+            item_image(item).as_deref(),
+            Some("https://bereghici.dev/images/october.png")
+        );
+
+        let item = &channel.items[1];
+        assert_eq!(
+            item.link.as_deref(),
+            Some("https://bereghici.dev/changelog/2024-09")
+        );
+        assert!(item.pub_date.is_some());
+    }
+
+    #[test]
+    fn it_scrapes_links() {
+        let base = Url::parse("https://bereghici.dev/changelog").unwrap();
+        let selectors = Selectors::new("h2 > a", None, None, None).unwrap();
+        let channel = read_channel(PAGE, &base, &selectors);
+
+        assert_eq!(channel.items.len(), 1);
+        assert_eq!(channel.items[0].title.as_deref(), Some("October release"));
+        assert!(Selectors::new("article[", None, None, None).is_err());
+    }
+}
@@ -1,89 +1,450 @@
-use chrono::{Months, NaiveDate, Utc};
+use chrono::{DateTime, Days, FixedOffset, Months, NaiveDate, Utc};
 use regex::Regex;
+use scraper::Selector;
+use std::{collections::HashMap, str::FromStr, sync::LazyLock};
 
-use crate::notion::models::{Page, PropertyValue, RichText};
+use super::{keywords::Keywords, scrape::Selectors};
+use crate::{
+    notion::models::{Date, Page, PropertyValue, RichText},
+    state::Snapshot,
+};
 
-#[derive(Debug)]
+pub const LAST_ITEM_DATE: &str = "Last item date";
+
+/// How the items of a source are fetched, from its `Type` select.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum SourceKind {
+    /// RSS or Atom feed, the default
+    #[default]
+    Feed,
+    /// Posts of a subreddit
+    Reddit,
+    /// Stories of the Hacker News front page or of a search
+    HackerNews,
+    /// Releases, tags or issues of a GitHub repository
+    GitHub,
+    /// Items of a web page without feed, found with CSS selectors
+    Scrape,
+    /// Changes of a web page
+    Watch,
+    /// Newsletters of a local Maildir directory or mbox file
+    Email,
+    /// Events of an iCalendar feed
+    Calendar,
+}
+
+impl FromStr for SourceKind {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "rss" | "atom" | "feed" => Ok(SourceKind::Feed),
+            "reddit" => Ok(SourceKind::Reddit),
+            "hacker news" | "hn" => Ok(SourceKind::HackerNews),
+            "github" => Ok(SourceKind::GitHub),
+            "scrape" => Ok(SourceKind::Scrape),
+            "watch" => Ok(SourceKind::Watch),
+            "email" | "newsletter" => Ok(SourceKind::Email),
+            "ics" | "ical" | "icalendar" | "calendar" => Ok(SourceKind::Calendar),
+            _ => Err(format!(
+                "{:?} is not a source type, expected RSS, Reddit, Hacker News, GitHub, Scrape, \
+                 Watch, Email or ICS",
+                input
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct Source {
+    pub page_id: String,
+    pub kind: SourceKind,
+    /// Title of the source page
+    pub name: Option<String>,
     pub link: String,
     pub offset_date: Option<NaiveDate>,
+    /// Publication date of the newest item ingested by a previous run
+    pub last_item_date: Option<DateTime<FixedOffset>>,
+    /// Whether the source page has a `Last item date` property to store it
+    pub has_last_item_date: bool,
+    /// Maximum number of new items added in a run
+    pub max_items: Option<usize>,
+    /// Only items matching these keywords are added
+    pub include: Option<Keywords>,
+    /// Items matching these keywords are skipped
+    pub exclude: Option<Keywords>,
+    /// Whether the article is fetched from the item link
+    pub full_text: bool,
+    /// Whether the image of the linked page is used as cover of the items without one
+    pub page_cover: bool,
+    /// Items with a lower score (Reddit score, Hacker News points) are excluded
+    pub min_score: Option<i64>,
+    /// Selectors of the items of `Scrape` sources
+    pub selectors: Option<Selectors>,
+    /// Part of the page watched by `Watch` sources
+    pub selector: Option<Selector>,
+    /// Text of the page of `Watch` sources on the previous run
+    pub snapshot: Option<Snapshot>,
+    /// Message-IDs of the newsletters of `Email` sources already processed
+    pub message_ids: Vec<String>,
+    /// Channel image, or favicon of the website when there are items to add
+    pub icon: Option<String>,
+    /// Title of the channel, set when fetching the source
+    pub channel_title: Option<String>,
 }
 
 impl Source {
-    pub fn new(page: &Page) -> Option<Source> {
-        let properties = page.properties.as_ref()?;
+    pub fn new(page: &Page) -> Result<Source, String> {
+        let properties = page
+            .properties
+            .as_ref()
+            .ok_or(format!("Source page {} has no properties", page.id))?;
 
         let link = match properties.get("Link") {
-            Some(PropertyValue::Url { url }) => match url {
-                Some(url) => Some(url.to_string()),
-                None => None,
-            },
+            Some(PropertyValue::Url { url: Some(url) }) => url.to_string(),
+            _ => return Err(format!("Source page {} has no link", page.id)),
+        };
+
+        let kind = match properties.get("Type") {
+            Some(PropertyValue::Select {
+                select: Some(option),
+            }) => option
+                .name
+                .parse::<SourceKind>()
+                .map_err(|err| format!("Invalid type for source {}: {}", link, err))?,
+            _ => SourceKind::Feed,
+        };
+
+        let name = match properties.get("Title") {
+            Some(PropertyValue::Title { title }) => text_content(title),
             _ => None,
         };
 
-        let offset_date = match properties.get("Offset date") {
-            Some(PropertyValue::Text { rich_text }) => match rich_text.first() {
-                Some(RichText::Text { rich_text: _, text }) => Some(&text.content),
-                None => None,
-            },
+        let offset = match properties.get("Offset date") {
+            Some(PropertyValue::Text { rich_text }) => {
+                match text_content(rich_text) {
+                    Some(offset) => Some(offset.parse::<Offset>().map_err(|err| {
+                        format!("Invalid offset date for source {}: {}", link, err)
+                    })?),
+                    None => None,
+                }
+            }
+            Some(PropertyValue::Date {
+                date: Some(Date {
+                    start: Some(start), ..
+                }),
+            }) => Some(Offset::Absolute(start.date_naive())),
             _ => None,
         };
 
-        let offset_date = match offset_date {
-            Some(offset_date) => extract_offset(offset_date).unwrap_or(0),
-            None => 0,
+        let offset_date = match offset {
+            Some(offset) => offset.since(Utc::now().date_naive()),
+            None => None,
         };
 
-        let offset_date = match offset_date {
-            offset if offset > 0 => Utc::now()
-                .date_naive()
-                .and_hms(0, 0, 0)
-                .date()
-                .checked_sub_months(Months::new(offset)),
+        let last_item_date = match properties.get(LAST_ITEM_DATE) {
+            Some(PropertyValue::Date { date: Some(date) }) => date.start,
             _ => None,
         };
 
-        if let Some(link) = link {
-            return Some(Self { link, offset_date });
-        }
+        let max_items = match properties.get("Max items") {
+            Some(PropertyValue::Number {
+                number: Some(number),
+            }) => Some(
+                number
+                    .as_u64()
+                    .filter(|max_items| *max_items > 0)
+                    .and_then(|max_items| usize::try_from(max_items).ok())
+                    .ok_or(format!(
+                        "Invalid max items for source {}: {} is not a positive integer",
+                        link, number
+                    ))?,
+            ),
+            _ => None,
+        };
 
-        None
+        let include = keywords(properties, "Include")
+            .map_err(|err| format!("Invalid include keywords for source {}: {}", link, err))?;
+        let exclude = keywords(properties, "Exclude")
+            .map_err(|err| format!("Invalid exclude keywords for source {}: {}", link, err))?;
+
+        let min_score = match properties.get("Min score") {
+            Some(PropertyValue::Number {
+                number: Some(number),
+            }) => Some(
+                number
+                    .as_f64()
+                    .map(|score| score.ceil() as i64)
+                    .ok_or(format!("Invalid min score for source {}: {}", link, number))?,
+            ),
+            _ => None,
+        };
+
+        let selectors = match kind {
+            SourceKind::Scrape => Some(
+                selectors(properties)
+                    .map_err(|err| format!("Invalid selectors for source {}: {}", link, err))?,
+            ),
+            _ => None,
+        };
+
+        let selector = match properties.get("Selector") {
+            Some(PropertyValue::Text { rich_text }) => match text_content(rich_text) {
+                Some(selector) => Some(
+                    Selector::parse(&selector)
+                        .map_err(|err| format!("Invalid selector for source {}: {}", link, err))?,
+                ),
+                None => None,
+            },
+            _ => None,
+        };
+
+        let full_text = matches!(
+            properties.get("Full text"),
+            Some(PropertyValue::Checkbox { checkbox: true })
+        );
+        let page_cover = matches!(
+            properties.get("Page cover"),
+            Some(PropertyValue::Checkbox { checkbox: true })
+        );
+
+        Ok(Self {
+            page_id: page.id.to_string(),
+            kind,
+            name,
+            link,
+            offset_date,
+            last_item_date,
+            has_last_item_date: properties.contains_key(LAST_ITEM_DATE),
+            max_items,
+            include,
+            exclude,
+            full_text,
+            page_cover,
+            min_score,
+            selectors,
+            selector,
+            snapshot: None,
+            message_ids: vec![],
+            icon: None,
+            channel_title: None,
+        })
+    }
+
+    /// Checks the texts of an item (title, description, categories) against the
+    /// `Include` and `Exclude` keywords.
+    pub fn accepts(&self, texts: &[&str]) -> bool {
+        let is_match = |keywords: &Keywords| texts.iter().any(|text| keywords.is_match(text));
+
+        self.include.as_ref().is_none_or(is_match) && !self.exclude.as_ref().is_some_and(is_match)
+    }
+
+    /// Checks the score of an item against the `Min score` of the source. Items
+    /// without a score are accepted.
+    pub fn accepts_score(&self, score: Option<i64>) -> bool {
+        self.min_score
+            .zip(score)
+            .is_none_or(|(min_score, score)| score >= min_score)
     }
 }
 
-fn extract_offset(input: &String) -> Option<u32> {
-    let months_re = Regex::new(r#"((^1 month$)|(^[2-9]|[1-9]\d+ months$))"#).unwrap();
-    let nr_re = Regex::new(r#"\d+"#).unwrap();
+/// Joins the segments of a text property, as they are.
+pub(super) fn plain_text(rich_text: &[RichText]) -> String {
+    rich_text
+        .iter()
+        .map(|RichText::Text { text, .. }| text.content.as_str())
+        .collect()
+}
 
-    if !months_re.is_match(input) {
-        return None;
+/// Joins the segments of a text property. Empty texts are ignored.
+pub(super) fn text_content(rich_text: &[RichText]) -> Option<String> {
+    match plain_text(rich_text).trim() {
+        "" => None,
+        content => Some(content.to_string()),
     }
+}
 
-    if let Some(offset) = nr_re.find(input) {
-        return Some(
-            offset
-                .as_str()
-                .to_string()
-                .trim()
-                .parse::<u32>()
-                .unwrap_or(0),
-        );
+fn selectors(properties: &HashMap<String, PropertyValue>) -> Result<Selectors, String> {
+    let text = |name: &str| match properties.get(name) {
+        Some(PropertyValue::Text { rich_text }) => text_content(rich_text),
+        _ => None,
+    };
+
+    let item = text("Item selector").ok_or("the Item selector is missing")?;
+
+    Selectors::new(
+        &item,
+        text("Title selector").as_deref(),
+        text("Link selector").as_deref(),
+        text("Date selector").as_deref(),
+    )
+}
+
+fn keywords(
+    properties: &HashMap<String, PropertyValue>,
+    name: &str,
+) -> Result<Option<Keywords>, String> {
+    match properties.get(name) {
+        Some(PropertyValue::Text { rich_text }) => match text_content(rich_text) {
+            Some(keywords) => Ok(Some(keywords.parse::<Keywords>()?)),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum OffsetUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Value of the `Offset date` property: either a period relative to the current
+/// date (`1 day`, `2 weeks`, `3 months`, `1 year`) or an ISO date (`2024-01-01`).
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Offset {
+    Relative { amount: u32, unit: OffsetUnit },
+    Absolute(NaiveDate),
+}
+
+impl Offset {
+    /// Returns the date from which items are considered.
+    pub fn since(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Offset::Absolute(date) => Some(date),
+            Offset::Relative { amount, unit } => match unit {
+                OffsetUnit::Day => today.checked_sub_days(Days::new(amount.into())),
+                OffsetUnit::Week => today.checked_sub_days(Days::new(u64::from(amount) * 7)),
+                OffsetUnit::Month => today.checked_sub_months(Months::new(amount)),
+                OffsetUnit::Year => today.checked_sub_months(Months::new(amount.checked_mul(12)?)),
+            },
+        }
     }
+}
+
+/// Offset relative to the current day, e.g. `2 weeks`.
+static RELATIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+) (day|week|month|year)(s?)$").unwrap());
+
+impl FromStr for Offset {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+            return Ok(Offset::Absolute(date));
+        }
 
-    None
+        let captures = RELATIVE_RE.captures(input).ok_or(format!(
+            "{:?} is neither a period (e.g. \"2 weeks\") nor a date (e.g. \"2024-01-01\")",
+            input
+        ))?;
+
+        let amount = captures[1]
+            .parse::<u32>()
+            .map_err(|_| format!("{:?} is too large", &captures[1]))?;
+
+        let unit = match &captures[2] {
+            "day" => OffsetUnit::Day,
+            "week" => OffsetUnit::Week,
+            "month" => OffsetUnit::Month,
+            _ => OffsetUnit::Year,
+        };
+
+        match (amount, captures[3].is_empty()) {
+            (0, _) => Err(format!("{:?} must be greater than zero", input)),
+            (1, false) => Err(format!("{:?} should be \"1 {}\"", input, &captures[2])),
+            (2.., true) => Err(format!("{:?} should be \"{}s\"", input, &captures[0])),
+            _ => Ok(Offset::Relative { amount, unit }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::extract_offset;
+    use super::{Offset, OffsetUnit, SourceKind};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(
+            Ok(Offset::Relative {
+                amount: 1,
+                unit: OffsetUnit::Month
+            }),
+            "1 month".parse::<Offset>()
+        );
+        assert!("0 month".parse::<Offset>().is_err());
+        assert!("1 months".parse::<Offset>().is_err());
+        assert!("-1 months".parse::<Offset>().is_err());
+        assert!("12 month".parse::<Offset>().is_err());
+        assert_eq!(
+            Ok(Offset::Relative {
+                amount: 12,
+                unit: OffsetUnit::Month
+            }),
+            "12 months".parse::<Offset>()
+        );
+        assert_eq!(
+            Ok(Offset::Relative {
+                amount: 2,
+                unit: OffsetUnit::Week
+            }),
+            "2 weeks".parse::<Offset>()
+        );
+        assert_eq!(
+            Ok(Offset::Relative {
+                amount: 10,
+                unit: OffsetUnit::Day
+            }),
+            " 10 days ".parse::<Offset>()
+        );
+        assert_eq!(
+            Ok(Offset::Relative {
+                amount: 1,
+                unit: OffsetUnit::Year
+            }),
+            "1 year".parse::<Offset>()
+        );
+        assert_eq!(
+            Ok(Offset::Absolute(
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+            )),
+            "2024-01-01".parse::<Offset>()
+        );
+        assert!("5".parse::<Offset>().is_err());
+        assert!("2 fortnights".parse::<Offset>().is_err());
+        assert!("2024-02-30".parse::<Offset>().is_err());
+        assert!("99999999999 days".parse::<Offset>().is_err());
+    }
 
     #[test]
-    fn test_extract_offset() {
-        assert_eq!(1, extract_offset(&"1 month".to_string()).unwrap());
-        assert!(extract_offset(&"0 month".to_string()).is_none());
-        assert!(extract_offset(&"1 months".to_string()).is_none());
-        assert!(extract_offset(&"-1 months".to_string()).is_none());
-        assert!(extract_offset(&"12 month".to_string()).is_none());
-        assert_eq!(12, extract_offset(&"12 months".to_string()).unwrap());
+    fn test_parse_source_kind() {
+        assert_eq!(Ok(SourceKind::Feed), "RSS".parse::<SourceKind>());
+        assert_eq!(
+            Ok(SourceKind::HackerNews),
+            "Hacker News".parse::<SourceKind>()
+        );
+        assert_eq!(Ok(SourceKind::GitHub), " github ".parse::<SourceKind>());
+        assert_eq!(Ok(SourceKind::Scrape), "Scrape".parse::<SourceKind>());
+        assert_eq!(Ok(SourceKind::Watch), "watch".parse::<SourceKind>());
+        assert_eq!(Ok(SourceKind::Email), "Newsletter".parse::<SourceKind>());
+        assert_eq!(Ok(SourceKind::Calendar), "ICS".parse::<SourceKind>());
+        assert!("Mastodon".parse::<SourceKind>().is_err());
+    }
+
+    #[test]
+    fn test_offset_since() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let since = |input: &str| input.parse::<Offset>().unwrap().since(today);
+
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 3, 21), since("10 days"));
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 3, 17), since("2 weeks"));
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 29), since("1 month"));
+        assert_eq!(NaiveDate::from_ymd_opt(2023, 3, 31), since("1 year"));
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 1), since("2024-01-01"));
     }
 }
@@ -0,0 +1,82 @@
+use std::fmt;
+
+use super::source::Source;
+
+#[derive(Debug, Default)]
+pub struct SourceSummary {
+    pub link: String,
+    pub added: usize,
+    /// Pages of events updated because the event changed
+    pub updated: usize,
+    /// Items left for a later run because of `Max items` or `--max-new-items`
+    pub deferred: usize,
+    pub failed: usize,
+    /// Items skipped because of the `Include` or `Exclude` keywords, or a
+    /// score under the `Min score`
+    pub excluded: usize,
+    /// Items skipped by the rules file
+    pub skipped: usize,
+}
+
+/// Counts of what happened to the items of each source during a run.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub sources: Vec<SourceSummary>,
+}
+
+impl Summary {
+    pub fn new(source_list: &[Source]) -> Summary {
+        Self {
+            sources: source_list
+                .iter()
+                .map(|source| SourceSummary {
+                    link: source.link.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn total(&self, count: fn(&SourceSummary) -> usize) -> usize {
+        self.sources.iter().map(count).sum()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Added {} items, updated {} ({} deferred, {} failed, {} excluded, {} skipped)",
+            self.total(|source| source.added),
+            self.total(|source| source.updated),
+            self.total(|source| source.deferred),
+            self.total(|source| source.failed),
+            self.total(|source| source.excluded),
+            self.total(|source| source.skipped),
+        )?;
+
+        for source in self.sources.iter().filter(|source| {
+            source.added
+                + source.updated
+                + source.deferred
+                + source.failed
+                + source.excluded
+                + source.skipped
+                > 0
+        }) {
+            writeln!(
+                f,
+                "  {}: {} added, {} updated, {} deferred, {} failed, {} excluded, {} skipped",
+                source.link,
+                source.added,
+                source.updated,
+                source.deferred,
+                source.failed,
+                source.excluded,
+                source.skipped
+            )?;
+        }
+
+        Ok(())
+    }
+}
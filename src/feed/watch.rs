@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use rss::{Channel, Item};
+use scraper::{node::Node, ElementRef, Html, Selector};
+use similar::{ChangeTag, TextDiff};
+
+use super::html::escape;
+use crate::state::Snapshot;
+
+/// Elements whose text is on its own lines.
+const BLOCK_TAGS: [&str; 27] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+];
+
+/// Reads a watched page, checked at `checked_at`. When its text changed since
+/// the previous snapshot, the channel has an item with the differences, linked
+/// to the page with the hash of the new text and the time of the check, so that
+/// every change is a new item, even back to an earlier text. Returns the
+/// snapshot of the page, which isn't compared on the first run.
+pub fn read_channel(
+    html: &str,
+    url: &Url,
+    name: Option<&str>,
+    selector: Option<&Selector>,
+    previous: Option<&Snapshot>,
+    checked_at: DateTime<Utc>,
+) -> (Channel, Snapshot) {
+    let document = Html::parse_document(html);
+
+    let title = name
+        .map(str::to_string)
+        .or_else(|| {
+            let selector = Selector::parse("title").unwrap();
+            document
+                .select(&selector)
+                .next()
+                .map(|title| text(title).join(" "))
+        })
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| url.to_string());
+
+    let lines = match selector {
+        Some(selector) => document.select(selector).flat_map(text).collect(),
+        None => text(document.root_element()),
+    };
+
+    let snapshot = Snapshot::new(lines.join("\n"));
+
+    let items = previous
+        .filter(|previous| previous.hash != snapshot.hash)
+        .map(|previous| {
+            let mut link = url.clone();
+            link.set_fragment(Some(&format!(
+                "{}-{}",
+                &snapshot.hash[..12],
+                checked_at.timestamp()
+            )));
+
+            Item {
+                title: Some(format!("{} changed", title)),
+                link: Some(link.to_string()),
+                description: Some(diff(&previous.text, &snapshot.text)),
+                pub_date: Some(checked_at.to_rfc2822()),
+                ..Default::default()
+            }
+        })
+        .into_iter()
+        .collect();
+
+    let channel = Channel {
+        title,
+        link: url.to_string(),
+        items,
+        ..Default::default()
+    };
+
+    (channel, snapshot)
+}
+
+/// Describes the changes between two versions of a page as HTML: the number of
+/// changed lines, and a unified diff of them.
+fn diff(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+
+    let (added, removed) = diff
+        .iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        });
+
+    format!(
+        "<p>{} added, {} removed.</p><pre>{}</pre>",
+        count(added, "line"),
+        count(removed, "line"),
+        escape(diff.unified_diff().context_radius(2).to_string().trim_end())
+    )
+}
+
+fn count(count: usize, unit: &str) -> String {
+    match count {
+        1 => format!("1 {}", unit),
+        count => format!("{} {}s", count, unit),
+    }
+}
+
+/// Returns the lines of text of an element, without scripts and styles.
+fn text(element: ElementRef) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+
+    write_text(element, &mut lines, &mut line);
+    end_line(&mut lines, &mut line);
+
+    lines
+}
+
+fn write_text(element: ElementRef, lines: &mut Vec<String>, line: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => line.push_str(text),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+
+                match child.value().name() {
+                    "script" | "style" | "noscript" | "template" | "svg" => {}
+                    "br" => end_line(lines, line),
+                    name if BLOCK_TAGS.contains(&name) => {
+                        end_line(lines, line);
+                        write_text(child, lines, line);
+                        end_line(lines, line);
+                    }
+                    _ => write_text(child, lines, line),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn end_line(lines: &mut Vec<String>, line: &mut String) {
+    let text = line.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    if !text.is_empty() {
+        lines.push(text);
+    }
+
+    line.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn page(price: &str) -> String {
+        format!(
+            r#"<html><head><title>Pricing</title><script>track()</script></head>
+            <body>
+                <nav>Home</nav>
+                <div id="plans">
+                    <h2>Pro</h2>
+                    <p>Only <b>{}</b> per month</p>
+                    <ul><li>Unlimited feeds</li><li>Priority support</li></ul>
+                </div>
+            </body></html>"#,
+            price
+        )
+    }
+
+    #[test]
+    fn it_detects_changes() {
+        let url = Url::parse("https://bereghici.dev/pricing").unwrap();
+        let selector = Selector::parse("#plans").unwrap();
+        let now = Utc::now();
+
+        let (channel, snapshot) = read_channel(&page("$5"), &url, None, Some(&selector), None, now);
+        assert!(channel.items.is_empty());
+        assert_eq!(
+            snapshot.text,
+            "Pro\nOnly $5 per month\nUnlimited feeds\nPriority support"
+        );
+
+        let (channel, same) = read_channel(
+            &page("$5"),
+            &url,
+            None,
+            Some(&selector),
+            Some(&snapshot),
+            now,
+        );
+        assert!(channel.items.is_empty());
+        assert_eq!(same, snapshot);
+
+        let (channel, changed) = read_channel(
+            &page("$7"),
+            &url,
+            None,
+            Some(&selector),
+            Some(&snapshot),
+            now,
+        );
+        let item = &channel.items[0];
+
+        assert_eq!(item.title.as_deref(), Some("Pricing changed"));
+        assert_eq!(
+            item.link.as_deref(),
+            Some(
+                format!(
+                    "https://bereghici.dev/pricing#{}-{}",
+                    &changed.hash[..12],
+                    now.timestamp()
+                )
+                .as_str()
+            )
+        );
+
+        let description = item.description.as_deref().unwrap();
+        assert!(description.starts_with("<p>1 line added, 1 line removed.</p><pre>"));
+        assert!(description.contains("-Only $5 per month\n+Only $7 per month"));
+
+        // the page going back and changing again is a new item
+        let (_, reverted) = read_channel(
+            &page("$5"),
+            &url,
+            None,
+            Some(&selector),
+            Some(&changed),
+            now,
+        );
+        let (channel, _) = read_channel(
+            &page("$7"),
+            &url,
+            None,
+            Some(&selector),
+            Some(&reverted),
+            now + Duration::days(1),
+        );
+        assert_ne!(channel.items[0].link, item.link);
+    }
+
+    #[test]
+    fn it_watches_the_whole_page_without_selector() {
+        let url = Url::parse("https://bereghici.dev/pricing").unwrap();
+        let (channel, snapshot) =
+            read_channel(&page("$5"), &url, Some("Plans"), None, None, Utc::now());
+
+        assert_eq!(channel.title, "Plans");
+        assert!(snapshot.text.starts_with("Pricing\nHome\nPro\n"));
+        assert!(!snapshot.text.contains("track()"));
+    }
+}
@@ -0,0 +1,198 @@
+use reqwest::Url;
+use rss::{extension::Extension, Item};
+
+/// Returns the Atom feed of a YouTube channel or playlist page, so sources can
+/// be added with the link of the channel instead of its feed.
+pub fn feed_link(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+
+    if !is_youtube(&url) {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?;
+
+    let query = match (segments.next(), segments.next()) {
+        (Some("channel"), Some(id)) if !id.is_empty() => ("channel_id", id.to_string()),
+        (Some("playlist"), _) => (
+            "playlist_id",
+            url.query_pairs()
+                .find(|(key, _)| key == "list")
+                .map(|(_, id)| id.to_string())?,
+        ),
+        _ => return None,
+    };
+
+    let mut feed = Url::parse("https://www.youtube.com/feeds/videos.xml").unwrap();
+    feed.query_pairs_mut().append_pair(query.0, &query.1);
+
+    Some(feed.to_string())
+}
+
+/// Returns the watch URL of a YouTube video item, embedded by Notion video
+/// blocks: from the `yt:videoId` of channel feeds, or from the item link.
+pub fn video_url(item: &Item) -> Option<String> {
+    let id = item
+        .extensions
+        .get("yt")
+        .into_iter()
+        .flat_map(|yt| yt.values().flatten())
+        .find(|extension| extension.name.ends_with("videoId"))
+        .and_then(|extension| extension.value.clone())
+        .or_else(|| item.link.as_deref().and_then(link_video_id))?;
+
+    Some(format!("https://www.youtube.com/watch?v={}", id.trim()))
+}
+
+/// Returns the view count of a video, from `media:statistics`.
+pub fn views(item: &Item) -> Option<u64> {
+    item.extensions
+        .get("media")
+        .into_iter()
+        .flat_map(|media| media.values().flatten())
+        .flat_map(descendants)
+        .find(|extension| extension.name.ends_with("statistics"))
+        .and_then(|extension| extension.attrs.get("views"))
+        .and_then(|views| views.parse::<u64>().ok())
+}
+
+fn descendants(extension: &Extension) -> Vec<&Extension> {
+    std::iter::once(extension)
+        .chain(extension.children.values().flatten().flat_map(descendants))
+        .collect()
+}
+
+fn link_video_id(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+
+    if url.host_str() == Some("youtu.be") {
+        return url
+            .path_segments()?
+            .next()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+    }
+
+    if !is_youtube(&url) {
+        return None;
+    }
+
+    match url.path() {
+        "/watch" => url
+            .query_pairs()
+            .find(|(key, _)| key == "v")
+            .map(|(_, id)| id.to_string()),
+        path => path
+            .strip_prefix("/shorts/")
+            .filter(|id| !id.is_empty())
+            .map(str::to_string),
+    }
+}
+
+fn is_youtube(url: &Url) -> bool {
+    matches!(
+        url.host_str(),
+        Some("youtube.com" | "www.youtube.com" | "m.youtube.com")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::atom::read_channel;
+
+    const CHANNEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+            <link rel="self" href="http://www.youtube.com/feeds/videos.xml?channel_id=UCaYhcUwRBNscFNUKTjgPFiA"/>
+            <id>yt:channel:aYhcUwRBNscFNUKTjgPFiA</id>
+            <yt:channelId>aYhcUwRBNscFNUKTjgPFiA</yt:channelId>
+            <title>Rust</title>
+            <link rel="alternate" href="https://www.youtube.com/channel/UCaYhcUwRBNscFNUKTjgPFiA"/>
+            <author><name>Rust</name><uri>https://www.youtube.com/channel/UCaYhcUwRBNscFNUKTjgPFiA</uri></author>
+            <published>2015-11-05T21:32:05+00:00</published>
+            <entry>
+                <id>yt:video:9Yg2lMGWkYc</id>
+                <yt:videoId>9Yg2lMGWkYc</yt:videoId>
+                <yt:channelId>UCaYhcUwRBNscFNUKTjgPFiA</yt:channelId>
+                <title>RustConf 2024 - Keynote</title>
+                <link rel="alternate" href="https://www.youtube.com/watch?v=9Yg2lMGWkYc"/>
+                <author><name>Rust</name><uri>https://www.youtube.com/channel/UCaYhcUwRBNscFNUKTjgPFiA</uri></author>
+                <published>2024-09-25T16:00:26+00:00</published>
+                <updated>2024-09-30T10:21:08+00:00</updated>
+                <media:group>
+                    <media:title>RustConf 2024 - Keynote</media:title>
+                    <media:content url="https://www.youtube.com/v/9Yg2lMGWkYc?version=3" type="application/x-shockwave-flash" width="640" height="390"/>
+                    <media:thumbnail url="https://i4.ytimg.com/vi/9Yg2lMGWkYc/hqdefault.jpg" width="480" height="360"/>
+                    <media:description>The opening keynote.
+
+Slides: https://rustconf.com &amp; more</media:description>
+                    <media:community>
+                        <media:starRating count="512" average="5.00" min="1" max="5"/>
+                        <media:statistics views="12345"/>
+                    </media:community>
+                </media:group>
+            </entry>
+        </feed>"#;
+
+    #[test]
+    fn it_reads_channel_feeds() {
+        let channel = read_channel(CHANNEL.as_bytes()).unwrap();
+        let item = &channel.items[0];
+
+        assert_eq!(
+            channel.link,
+            "https://www.youtube.com/channel/UCaYhcUwRBNscFNUKTjgPFiA"
+        );
+        assert_eq!(item.author.as_deref(), Some("Rust"));
+        assert_eq!(
+            item.description.as_deref(),
+            Some("<p>The opening keynote.</p><p>Slides: https://rustconf.com &amp; more</p>")
+        );
+        assert_eq!(
+            video_url(item).as_deref(),
+            Some("https://www.youtube.com/watch?v=9Yg2lMGWkYc")
+        );
+        assert_eq!(views(item), Some(12345));
+        assert_eq!(
+            crate::feed::cover::item_image(item).as_deref(),
+            Some("https://i4.ytimg.com/vi/9Yg2lMGWkYc/hqdefault.jpg")
+        );
+    }
+
+    #[test]
+    fn it_finds_video_links() {
+        let item = |link: &str| Item {
+            link: Some(link.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            video_url(&item("https://youtu.be/9Yg2lMGWkYc?t=42")).as_deref(),
+            Some("https://www.youtube.com/watch?v=9Yg2lMGWkYc")
+        );
+        assert_eq!(
+            video_url(&item("https://m.youtube.com/shorts/9Yg2lMGWkYc")).as_deref(),
+            Some("https://www.youtube.com/watch?v=9Yg2lMGWkYc")
+        );
+        assert_eq!(video_url(&item("https://bereghici.dev/watch?v=1")), None);
+    }
+
+    #[test]
+    fn it_finds_channel_feeds() {
+        assert_eq!(
+            feed_link("https://www.youtube.com/channel/UCaYhcUwRBNscFNUKTjgPFiA/videos").as_deref(),
+            Some("https://www.youtube.com/feeds/videos.xml?channel_id=UCaYhcUwRBNscFNUKTjgPFiA")
+        );
+        assert_eq!(
+            feed_link("https://youtube.com/playlist?list=PL85XCvVPmGQ").as_deref(),
+            Some("https://www.youtube.com/feeds/videos.xml?playlist_id=PL85XCvVPmGQ")
+        );
+        assert_eq!(
+            feed_link(
+                "https://www.youtube.com/feeds/videos.xml?channel_id=UCaYhcUwRBNscFNUKTjgPFiA"
+            ),
+            None
+        );
+        assert_eq!(feed_link("https://bereghici.dev/channel/1"), None);
+    }
+}
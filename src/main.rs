@@ -1,12 +1,13 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use config::Config;
-use feed::Feed;
+use feed::{opml, records, Feed};
 use notion::Client;
 use std::{error::Error, process};
 
 mod config;
 mod feed;
 mod notion;
+mod state;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about)]
@@ -15,6 +16,52 @@ struct Arguments {
     notion_source_database_id: Option<String>,
     #[clap(long)]
     notion_feed_database_id: Option<String>,
+    /// IANA time zone used to display publication dates, e.g. Europe/Bucharest
+    #[clap(long)]
+    timezone: Option<String>,
+    /// JSON file used to remember the newest ingested item of each source between runs
+    #[clap(long)]
+    state_file: Option<String>,
+    /// Maximum number of new items added in a run, newest first. Others are added by later runs
+    #[clap(long)]
+    max_new_items: Option<String>,
+    /// TOML or YAML file of rules applied to new items (tags, starred, priority, routing)
+    #[clap(long)]
+    rules_file: Option<String>,
+    /// Emoji used as icon of the feed pages whose source has no favicon, e.g. 📰
+    #[clap(long)]
+    icon_emoji: Option<String>,
+    /// Names of the optional feed properties, e.g. "author=Writer, tags=Topics, comments="
+    #[clap(long)]
+    property_names: Option<String>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Adds the feeds of an OPML file (e.g. exported from Feedly or Inoreader) to the Sources database
+    ImportOpml { file: String },
+    /// Writes the sources of the Sources database to an OPML file
+    ExportOpml { file: String },
+    /// Writes the pages of a database to a CSV or JSON file
+    Export {
+        /// `sources`, `feed` or the ID of another database
+        database: String,
+        file: String,
+        /// Columns of the file and the properties they hold, e.g. "Name=Title, URL=Link"
+        #[clap(long, value_parser = records::Mapping::new)]
+        mapping: Option<records::Mapping>,
+    },
+    /// Creates pages of a database from the records of a CSV or JSON file
+    Import {
+        /// `sources`, `feed` or the ID of another database
+        database: String,
+        file: String,
+        /// Columns of the file and the properties they hold, e.g. "Name=Title, URL=Link"
+        #[clap(long, value_parser = records::Mapping::new)]
+        mapping: Option<records::Mapping>,
+    },
 }
 
 #[tokio::main]
@@ -22,17 +69,97 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let args = Arguments::parse();
 
     let config = Config::new(args.notion_source_database_id, args.notion_feed_database_id)
+        .and_then(|config| config.with_timezone(args.timezone))
+        .map(|config| config.with_state_file(args.state_file))
+        .and_then(|config| config.with_max_new_items(args.max_new_items))
+        .map(|config| config.with_rules_file(args.rules_file))
+        .map(|config| config.with_icon_emoji(args.icon_emoji))
+        .and_then(|config| config.with_property_names(args.property_names))
         .unwrap_or_else(|err| {
             eprintln!("Failed to create application config: {}", err);
             process::exit(1)
         });
 
     let notion_client = Client::new(&config).unwrap_or_else(|err| {
-        eprintln!("{}", format!("Failed to create the notion client: {}", err));
+        eprintln!("Failed to create the notion client: {}", err);
         process::exit(1)
     });
 
-    Feed::new(&notion_client).run().await.unwrap_or_else(|err| {
+    match args.command {
+        Some(Command::ImportOpml { file }) => {
+            let import = opml::import(&notion_client, &file)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to import {}: {}", file, err);
+                    process::exit(1)
+                });
+
+            for failure in import.failed.iter() {
+                eprintln!("{}", failure);
+            }
+
+            print!("{}", import);
+            process::exit(0)
+        }
+        Some(Command::ExportOpml { file }) => {
+            let count = opml::export(&notion_client, &file)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to export {}: {}", file, err);
+                    process::exit(1)
+                });
+
+            println!("Exported {} sources to {}", count, file);
+            process::exit(0)
+        }
+        Some(Command::Export {
+            database,
+            file,
+            mapping,
+        }) => {
+            let count = records::export(
+                &notion_client,
+                records::database(&database),
+                &file,
+                mapping.as_ref(),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to export {}: {}", file, err);
+                process::exit(1)
+            });
+
+            println!("Exported {} pages to {}", count, file);
+            process::exit(0)
+        }
+        Some(Command::Import {
+            database,
+            file,
+            mapping,
+        }) => {
+            let import = records::import(
+                &notion_client,
+                records::database(&database),
+                &file,
+                mapping.as_ref(),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to import {}: {}", file, err);
+                process::exit(1)
+            });
+
+            for failure in import.failed.iter() {
+                eprintln!("{}", failure);
+            }
+
+            print!("{}", import);
+            process::exit(0)
+        }
+        None => {}
+    }
+
+    let summary = Feed::new(&notion_client).run().await.unwrap_or_else(|err| {
         eprintln!(
             "{}",
             format!("An error has occurred while processing data: {}", err)
@@ -40,5 +167,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         process::exit(1)
     });
 
+    print!("{}", summary);
+
     process::exit(0)
 }
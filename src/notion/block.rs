@@ -0,0 +1,268 @@
+use super::{
+    models::{Block, BlockObject},
+    Client,
+};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, future::Future, mem};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Blocks {
+    pub object: String,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    pub results: Vec<BlockObject>,
+}
+
+impl Blocks {
+    /// Returns the cursor of the next page of children, if there is one.
+    pub fn next_page(&self) -> Option<&str> {
+        self.next_cursor.as_deref().filter(|_| self.has_more)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AppendBlockChildrenProps {
+    children: Vec<Block>,
+}
+
+impl<'a> Client<'a> {
+    /// Returns all the children of a page or a block, following the pagination.
+    pub async fn retrieve_block_children(
+        &self,
+        block_id: &str,
+    ) -> Result<Vec<BlockObject>, Box<dyn Error>> {
+        follow_pages(|start_cursor| self.retrieve_block_children_page(block_id, start_cursor)).await
+    }
+
+    /// Returns a page of the children of a page or a block, from its cursor.
+    async fn retrieve_block_children_page(
+        &self,
+        block_id: &str,
+        start_cursor: Option<String>,
+    ) -> Result<Blocks, Box<dyn Error>> {
+        let path = format!("/blocks/{}/children", block_id);
+        let mut req = self
+            .build_request(Method::GET, path)
+            .query(&[("page_size", Block::MAX_CHILDREN.to_string())]);
+
+        if let Some(start_cursor) = &start_cursor {
+            req = req.query(&[("start_cursor", start_cursor)]);
+        }
+
+        Ok(req
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Blocks>()
+            .await?)
+    }
+
+    /// Appends blocks to a page or a block, in several requests when there are
+    /// more than Notion accepts at once. Returns the created blocks.
+    pub async fn append_block_children(
+        &self,
+        block_id: &str,
+        children: Vec<Block>,
+    ) -> Result<Vec<BlockObject>, Box<dyn Error>> {
+        let path = format!("/blocks/{}/children", block_id);
+        let mut created = vec![];
+        let mut children = children;
+
+        while !children.is_empty() {
+            let rest = children.split_off(Block::fit(&children));
+
+            let blocks = self
+                .build_request(Method::PATCH, &path)
+                .json(&AppendBlockChildrenProps {
+                    children: mem::replace(&mut children, rest),
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Blocks>()
+                .await?;
+
+            created.extend(blocks.results);
+        }
+
+        Ok(created)
+    }
+
+    /// Moves a block to the trash.
+    pub async fn delete_block(&self, block_id: &str) -> Result<BlockObject, Box<dyn Error>> {
+        let path = format!("/blocks/{}", block_id);
+
+        let res = self
+            .build_request(Method::DELETE, path)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json::<BlockObject>().await?)
+    }
+
+    /// Deletes all the children of a page or a block. Notion has no request
+    /// deleting several blocks, so there's a request per child, sent one after
+    /// the other to stay within the rate limit: this is meant for short bodies,
+    /// like the ones of events.
+    pub async fn delete_block_children(&self, block_id: &str) -> Result<(), Box<dyn Error>> {
+        for child in self.retrieve_block_children(block_id).await? {
+            self.delete_block(&child.id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Requests the pages of a list from the first one, with the cursor of the
+/// next page, until the last one. Returns the results of all the pages.
+async fn follow_pages<F, Fut>(mut fetch: F) -> Result<Vec<BlockObject>, Box<dyn Error>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Blocks, Box<dyn Error>>>,
+{
+    let mut results = vec![];
+    let mut start_cursor = None;
+
+    loop {
+        let blocks = fetch(start_cursor).await?;
+
+        start_cursor = blocks.next_page().map(str::to_string);
+        results.extend(blocks.results);
+
+        if start_cursor.is_none() {
+            return Ok(results);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notion::models::{EmptyBlock, ExternalFile, File, TextBlock, UrlBlock};
+
+    #[test]
+    fn it_deserializes_block_children() {
+        let blocks = serde_json::from_str::<Blocks>(
+            r#"{
+                "object": "list",
+                "next_cursor": "cursor",
+                "has_more": true,
+                "results": [
+                    {
+                        "object": "block",
+                        "id": "paragraph-id",
+                        "has_children": false,
+                        "type": "paragraph",
+                        "paragraph": {
+                            "rich_text": [{
+                                "type": "text",
+                                "text": { "content": "Hello", "link": null },
+                                "annotations": { "bold": true, "italic": false, "strikethrough": false, "underline": false, "code": false, "color": "default" },
+                                "plain_text": "Hello",
+                                "href": null
+                            }],
+                            "color": "default"
+                        }
+                    },
+                    { "object": "block", "id": "divider-id", "type": "divider", "divider": {} },
+                    { "object": "block", "id": "bookmark-id", "type": "bookmark", "bookmark": { "caption": [], "url": "https://bereghici.dev" } },
+                    { "object": "block", "id": "video-id", "type": "video", "video": { "type": "external", "external": { "url": "https://youtu.be/id" } } },
+                    { "object": "block", "id": "table-id", "has_children": true, "type": "table", "table": { "table_width": 2 } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(blocks.has_more);
+        assert!(matches!(
+            &blocks.results[0].block,
+            Block::Paragraph { paragraph: TextBlock { rich_text, .. } } if rich_text.len() == 1
+        ));
+        assert_eq!(
+            blocks
+                .results
+                .into_iter()
+                .skip(1)
+                .map(|block| block.block)
+                .collect::<Vec<Block>>(),
+            vec![
+                Block::Divider {
+                    divider: EmptyBlock {}
+                },
+                Block::Bookmark {
+                    bookmark: UrlBlock {
+                        url: "https://bereghici.dev".to_string(),
+                        caption: vec![],
+                    }
+                },
+                Block::Video {
+                    video: File::External {
+                        external: ExternalFile {
+                            url: "https://youtu.be/id".to_string()
+                        }
+                    }
+                },
+                Block::Unsupported,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_follows_block_children_pages() {
+        let pages = [
+            r#"{
+                "object": "list",
+                "next_cursor": "second-page",
+                "has_more": true,
+                "results": [
+                    { "object": "block", "id": "first-id", "type": "divider", "divider": {} }
+                ]
+            }"#,
+            r#"{
+                "object": "list",
+                "next_cursor": "third-page",
+                "has_more": true,
+                "results": [
+                    { "object": "block", "id": "second-id", "type": "divider", "divider": {} }
+                ]
+            }"#,
+            // the cursor is only followed while Notion has more children
+            r#"{
+                "object": "list",
+                "next_cursor": "fourth-page",
+                "has_more": false,
+                "results": [
+                    { "object": "block", "id": "third-id", "type": "divider", "divider": {} }
+                ]
+            }"#,
+        ];
+        let mut cursors = vec![];
+
+        let children = follow_pages(|start_cursor| {
+            let page = pages[cursors.len()];
+            cursors.push(start_cursor);
+
+            async move { Ok(serde_json::from_str::<Blocks>(page)?) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            cursors,
+            vec![
+                None,
+                Some("second-page".to_string()),
+                Some("third-page".to_string())
+            ]
+        );
+        assert_eq!(
+            children
+                .iter()
+                .map(|block| block.id.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["first-id", "second-id", "third-id"]
+        );
+    }
+}
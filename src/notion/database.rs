@@ -1,11 +1,25 @@
 use super::{models::Page, Client};
+use crate::config::Config;
 use reqwest::{Error, Method};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+#[derive(Debug, Clone)]
 pub enum DatabaseKind {
     Source,
     Feed,
+    /// Any other database, by ID
+    Id(String),
+}
+
+impl DatabaseKind {
+    pub fn id<'a>(&'a self, config: &'a Config) -> &'a str {
+        match self {
+            DatabaseKind::Source => &config.notion_source_database_id,
+            DatabaseKind::Feed => &config.notion_feed_database_id,
+            DatabaseKind::Id(id) => id,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,14 +45,14 @@ pub enum FilterKind {
     },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Filter {
     pub property: String,
     #[serde(flatten)]
     pub kind: FilterKind,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum DatabaseFilter {
     Property {
@@ -73,18 +87,48 @@ pub struct Pages {
     pub results: Vec<Page>,
 }
 
+/// Type of a database property, e.g. `rich_text`, `select` or `relation`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PropertySchema {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub property_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Database {
+    pub id: String,
+    pub properties: HashMap<String, PropertySchema>,
+}
+
+impl Database {
+    /// Returns the type of a property, if the database has it.
+    pub fn property_type(&self, name: &str) -> Option<&str> {
+        self.properties
+            .get(name)
+            .map(|property| property.property_type.as_str())
+    }
+}
+
 impl<'a> Client<'a> {
+    pub async fn retrieve_database(&self, kind: DatabaseKind) -> Result<Database, Error> {
+        let path = format!("/databases/{}", kind.id(self.config));
+
+        let res = self
+            .build_request(Method::GET, path)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        res.json::<Database>().await
+    }
+
     pub async fn query_database(
         &self,
         kind: DatabaseKind,
         query: Option<DatabaseQuery>,
     ) -> Result<Pages, Error> {
-        let db_id = match kind {
-            DatabaseKind::Source => &self.config.notion_source_database_id,
-            DatabaseKind::Feed => &self.config.notion_feed_database_id,
-        };
-
-        let path = format!("/databases/{}/query", db_id);
+        let path = format!("/databases/{}/query", kind.id(self.config));
         let mut req = self.build_request(Method::POST, path);
 
         if let Some(query) = query {
@@ -100,7 +144,6 @@ impl<'a> Client<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
 
     #[tokio::test]
     async fn it_query_database() {
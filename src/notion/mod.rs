@@ -1,3 +1,4 @@
+pub mod block;
 mod client;
 pub mod database;
 pub mod models;
@@ -1,5 +1,7 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use serde_json::Number;
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -20,6 +22,20 @@ pub struct Text {
     pub link: Option<Link>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone)]
+pub struct Annotations {
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub strikethrough: bool,
+    #[serde(default)]
+    pub underline: bool,
+    #[serde(default)]
+    pub code: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
@@ -28,14 +44,205 @@ pub enum RichText {
         #[serde(flatten)]
         rich_text: Option<RichTextProperties>,
         text: Text,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
-#[serde(rename_all = "snake_case")]
+impl RichText {
+    /// Maximum length of the content of a rich text object
+    /// Maximum length of the content of a rich text object, in UTF-16 code units
+    pub const MAX_LENGTH: usize = 2000;
+    /// Maximum number of rich text objects in a block
+    pub const MAX_OBJECTS: usize = 100;
+
+    /// Creates plain text, split in several rich text objects if it's too long.
+    pub fn plain(content: &str) -> Vec<RichText> {
+        Self::new(content, None, None)
+    }
+
+    /// Creates formatted text, split in several rich text objects if it's too long.
+    pub fn new(
+        content: &str,
+        annotations: Option<Annotations>,
+        link: Option<String>,
+    ) -> Vec<RichText> {
+        let mut chunks = vec![];
+        let mut chunk = String::new();
+        let mut length = 0;
+
+        // characters outside the Basic Multilingual Plane, like emoji, count twice
+        for char in content.chars() {
+            if length + char.len_utf16() > Self::MAX_LENGTH {
+                chunks.push(std::mem::take(&mut chunk));
+                length = 0;
+            }
+
+            chunk.push(char);
+            length += char.len_utf16();
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        chunks
+            .into_iter()
+            .map(|chunk| RichText::Text {
+                rich_text: None,
+                text: Text {
+                    content: chunk,
+                    link: link.clone().map(|url| Link { url }),
+                },
+                annotations: annotations.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(try_from = "DateValue")]
 pub struct Date {
-    pub start: Option<DateTime<Utc>>,
-    pub end: Option<DateTime<Utc>>,
+    pub start: Option<DateTime<FixedOffset>>,
+    pub end: Option<DateTime<FixedOffset>>,
+    /// IANA time zone used by Notion to display the date
+    pub time_zone: Option<String>,
+    /// Whether the dates have no time, like the days of all-day events
+    pub all_day: bool,
+}
+
+impl Date {
+    pub fn new(start: DateTime<FixedOffset>, time_zone: Option<Tz>) -> Date {
+        Self::range(start, None, time_zone)
+    }
+
+    /// Creates a date range, like the start and end of an event.
+    pub fn range(
+        start: DateTime<FixedOffset>,
+        end: Option<DateTime<FixedOffset>>,
+        time_zone: Option<Tz>,
+    ) -> Date {
+        match time_zone {
+            Some(time_zone) => Self {
+                start: Some(start.with_timezone(&time_zone).fixed_offset()),
+                end: end.map(|end| end.with_timezone(&time_zone).fixed_offset()),
+                time_zone: Some(time_zone.name().to_string()),
+                all_day: false,
+            },
+            None => Self {
+                start: Some(start),
+                end,
+                time_zone: None,
+                all_day: false,
+            },
+        }
+    }
+
+    /// Creates a range of days, sent without time so that they're the same
+    /// days in every time zone.
+    pub fn days(start: NaiveDate, end: Option<NaiveDate>) -> Date {
+        let midnight = |date: NaiveDate| date.and_time(NaiveTime::MIN).and_utc().fixed_offset();
+
+        Self {
+            start: Some(midnight(start)),
+            end: end.map(midnight),
+            time_zone: None,
+            all_day: true,
+        }
+    }
+}
+
+/// Date as returned by Notion, without time (`2024-01-01`) when the time is
+/// not set.
+#[derive(Deserialize)]
+struct DateValue {
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default)]
+    end: Option<String>,
+    #[serde(default)]
+    time_zone: Option<String>,
+}
+
+impl TryFrom<DateValue> for Date {
+    type Error = String;
+
+    fn try_from(value: DateValue) -> Result<Self, Self::Error> {
+        let parse = |date: Option<String>| match date {
+            Some(date) => parse_date(&date).map(Some),
+            None => Ok(None),
+        };
+
+        Ok(Self {
+            all_day: value
+                .start
+                .as_deref()
+                .is_some_and(|start| !start.contains('T')),
+            start: parse(value.start)?,
+            end: parse(value.end)?,
+            time_zone: value.time_zone,
+        })
+    }
+}
+
+fn parse_date(date: &str) -> Result<DateTime<FixedOffset>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date);
+    }
+
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+        .map_err(|_| format!("invalid date: {:?}", date))
+}
+
+impl Serialize for Date {
+    /// Notion rejects dates carrying both an UTC offset and a `time_zone`,
+    /// so the local time is sent without offset when a time zone is set.
+    /// Days are sent without time.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let format = |date: &Option<DateTime<FixedOffset>>| {
+            date.map(|date| match (self.all_day, &self.time_zone) {
+                (true, _) => date.format("%Y-%m-%d").to_string(),
+                (false, Some(_)) => date.naive_local().format("%Y-%m-%dT%H:%M:%S").to_string(),
+                (false, None) => date.to_rfc3339(),
+            })
+        };
+
+        let mut state = serializer.serialize_struct("Date", 3)?;
+        state.serialize_field("start", &format(&self.start))?;
+        state.serialize_field("end", &format(&self.end))?;
+        if let Some(time_zone) = self.time_zone.as_ref().filter(|_| !self.all_day) {
+            state.serialize_field("time_zone", time_zone)?;
+        }
+        state.end()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct SelectOption {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl SelectOption {
+    /// Commas aren't allowed in the names of options, they are replaced by
+    /// spaces.
+    pub fn new(name: &str) -> SelectOption {
+        Self {
+            id: None,
+            name: name.replace(',', " "),
+            color: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct Relation {
+    /// ID of the related page
+    pub id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -55,6 +262,15 @@ pub enum PropertyValue {
     Checkbox {
         checkbox: bool,
     },
+    Number {
+        number: Option<Number>,
+    },
+    Select {
+        select: Option<SelectOption>,
+    },
+    MultiSelect {
+        multi_select: Vec<SelectOption>,
+    },
     Formula {
         expression: Option<String>,
     },
@@ -64,6 +280,203 @@ pub enum PropertyValue {
     CreatedTime {
         created_time: DateTime<Utc>,
     },
+    Relation {
+        relation: Vec<Relation>,
+    },
+    /// Properties of other types, e.g. `status` or `people`
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct ExternalFile {
+    pub url: String,
+}
+
+/// File uploaded to Notion, its URL expires after an hour.
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct HostedFile {
+    pub url: String,
+    pub expiry_time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum File {
+    External { external: ExternalFile },
+    File { file: HostedFile },
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum Icon {
+    Emoji { emoji: String },
+    External { external: ExternalFile },
+    File { file: HostedFile },
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct TextBlock {
+    pub rich_text: Vec<RichText>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Block>,
+}
+
+impl TextBlock {
+    pub fn new(rich_text: Vec<RichText>) -> TextBlock {
+        Self {
+            rich_text,
+            children: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct CodeBlock {
+    pub rich_text: Vec<RichText>,
+    pub language: String,
+}
+
+/// Content of bookmark and embed blocks.
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct UrlBlock {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub caption: Vec<RichText>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct CalloutBlock {
+    pub rich_text: Vec<RichText>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Icon>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Block>,
+}
+
+/// Content of blocks without properties, like dividers.
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone)]
+pub struct EmptyBlock {}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum Block {
+    Paragraph {
+        paragraph: TextBlock,
+    },
+    #[serde(rename = "heading_1")]
+    Heading1 {
+        heading_1: TextBlock,
+    },
+    #[serde(rename = "heading_2")]
+    Heading2 {
+        heading_2: TextBlock,
+    },
+    #[serde(rename = "heading_3")]
+    Heading3 {
+        heading_3: TextBlock,
+    },
+    BulletedListItem {
+        bulleted_list_item: TextBlock,
+    },
+    NumberedListItem {
+        numbered_list_item: TextBlock,
+    },
+    Quote {
+        quote: TextBlock,
+    },
+    Code {
+        code: CodeBlock,
+    },
+    Image {
+        image: File,
+    },
+    Bookmark {
+        bookmark: UrlBlock,
+    },
+    Embed {
+        embed: UrlBlock,
+    },
+    Video {
+        video: File,
+    },
+    Audio {
+        audio: File,
+    },
+    Divider {
+        divider: EmptyBlock,
+    },
+    Callout {
+        callout: CalloutBlock,
+    },
+    Toggle {
+        toggle: TextBlock,
+    },
+    /// Any other block type, which can't be created
+    #[serde(other)]
+    Unsupported,
+}
+
+impl Block {
+    /// Maximum number of blocks sent in a request
+    pub const MAX_CHILDREN: usize = 100;
+    /// Maximum number of blocks sent in a request, nested blocks included
+    pub const MAX_BLOCKS: usize = 1000;
+    /// Maximum levels of nested blocks sent in a request
+    pub const MAX_DEPTH: usize = 2;
+
+    /// Number of blocks sent for the block: itself and its nested blocks.
+    pub fn count(&self) -> usize {
+        let children = match self {
+            Block::Paragraph { paragraph: block }
+            | Block::Heading1 { heading_1: block }
+            | Block::Heading2 { heading_2: block }
+            | Block::Heading3 { heading_3: block }
+            | Block::BulletedListItem {
+                bulleted_list_item: block,
+            }
+            | Block::NumberedListItem {
+                numbered_list_item: block,
+            }
+            | Block::Quote { quote: block }
+            | Block::Toggle { toggle: block } => &block.children,
+            Block::Callout { callout } => &callout.children,
+            _ => return 1,
+        };
+
+        1 + children.iter().map(Block::count).sum::<usize>()
+    }
+
+    /// Returns how many of the first blocks are sent in a request: at most
+    /// `MAX_CHILDREN`, and `MAX_BLOCKS` with their nested blocks.
+    pub fn fit(blocks: &[Block]) -> usize {
+        let mut count = 0;
+
+        let fit = blocks
+            .iter()
+            .take(Self::MAX_CHILDREN)
+            .take_while(|block| {
+                count += block.count();
+                count <= Self::MAX_BLOCKS
+            })
+            .count();
+
+        // a block over the limit is sent alone, and rejected
+        fit.max(blocks.len().min(1))
+    }
+}
+
+/// A block returned by Notion.
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct BlockObject {
+    pub id: String,
+    #[serde(default)]
+    pub has_children: bool,
+    #[serde(flatten)]
+    pub block: Block,
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -82,3 +495,147 @@ pub struct Page {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, PropertyValue>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_deserializes_dates_with_and_without_time() {
+        let date: Date =
+            serde_json::from_str(r#"{"start": "2024-01-01", "end": null, "time_zone": null}"#)
+                .unwrap();
+        assert_eq!(
+            date.start.unwrap().to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(date.end, None);
+        assert!(date.all_day);
+
+        let date: Date = serde_json::from_str(
+            r#"{"start": "2024-01-01T10:30:00.000+02:00", "end": "2024-01-02"}"#,
+        )
+        .unwrap();
+        assert!(!date.all_day);
+        assert_eq!(
+            date.start.unwrap().to_rfc3339(),
+            "2024-01-01T10:30:00+02:00"
+        );
+        assert_eq!(date.end.unwrap().to_rfc3339(), "2024-01-02T00:00:00+00:00");
+
+        assert!(serde_json::from_str::<Date>(r#"{"start": "tomorrow"}"#).is_err());
+    }
+
+    #[test]
+    fn it_serializes_days_without_time() {
+        let start = NaiveDate::from_ymd_opt(2024, 9, 12).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 9, 13).unwrap();
+
+        let date = serde_json::to_value(Date::days(start, Some(end))).unwrap();
+        assert_eq!(date["start"], "2024-09-12");
+        assert_eq!(date["end"], "2024-09-13");
+        assert!(date.get("time_zone").is_none());
+
+        let date: Date = serde_json::from_value(date).unwrap();
+        assert_eq!(date, Date::days(start, Some(end)));
+    }
+
+    #[test]
+    fn it_deserializes_pages_with_unsupported_properties() {
+        let page: Page = serde_json::from_str(
+            r#"{
+                "id": "page",
+                "archived": false,
+                "parent": null,
+                "properties": {
+                    "Link": {"type": "url", "url": "https://bereghici.dev"},
+                    "Status": {"type": "status", "status": {"name": "Done"}},
+                    "Owner": {"type": "people", "people": []}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let properties = page.properties.unwrap();
+        assert_eq!(properties.get("Status"), Some(&PropertyValue::Unsupported));
+        assert_eq!(properties.get("Owner"), Some(&PropertyValue::Unsupported));
+        assert_eq!(
+            properties.get("Link"),
+            Some(&PropertyValue::Url {
+                url: Some("https://bereghici.dev".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn it_fits_nested_blocks_in_requests() {
+        let item = |children: Vec<Block>| Block::BulletedListItem {
+            bulleted_list_item: TextBlock {
+                rich_text: RichText::plain("item"),
+                children,
+            },
+        };
+        let blocks = vec![item(vec![item(vec![]); 20]); Block::MAX_CHILDREN];
+
+        assert_eq!(blocks[0].count(), 21);
+        assert_eq!(Block::fit(&blocks), 47);
+        assert_eq!(Block::fit(&blocks[..10]), 10);
+        assert_eq!(Block::fit(&[item(vec![item(vec![]); 1000])]), 1);
+        assert_eq!(Block::fit(&[]), 0);
+    }
+
+    #[test]
+    fn it_replaces_commas_in_select_options() {
+        assert_eq!(SelectOption::new("Rust, Go").name, "Rust  Go");
+    }
+
+    #[test]
+    fn it_splits_long_plain_text() {
+        let rich_text = RichText::plain(&"é".repeat(RichText::MAX_LENGTH * 2 + 1));
+
+        assert_eq!(rich_text.len(), 3);
+        assert!(matches!(
+            &rich_text[2],
+            RichText::Text { text, .. } if text.content == "é"
+        ));
+        assert!(RichText::plain("").is_empty());
+
+        let rich_text = RichText::plain(&"🦀".repeat(RichText::MAX_LENGTH / 2 + 1));
+
+        assert_eq!(rich_text.len(), 2);
+        assert!(matches!(
+            &rich_text[1],
+            RichText::Text { text, .. } if text.content == "🦀"
+        ));
+    }
+
+    #[test]
+    fn it_serializes_blocks() {
+        let block = Block::Heading1 {
+            heading_1: TextBlock::new(RichText::plain("Title")),
+        };
+
+        assert_eq!(
+            serde_json::to_value(block).unwrap(),
+            serde_json::json!({
+                "type": "heading_1",
+                "heading_1": {
+                    "rich_text": [{ "type": "text", "text": { "content": "Title" } }]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn it_serializes_dates_in_time_zone() {
+        let start = DateTime::parse_from_rfc3339("2022-09-05T10:00:00-05:00").unwrap();
+
+        let date = serde_json::to_value(Date::new(start, None)).unwrap();
+        assert_eq!(date["start"], "2022-09-05T10:00:00-05:00");
+        assert!(date.get("time_zone").is_none());
+
+        let date = serde_json::to_value(Date::new(start, Some(Tz::Europe__Bucharest))).unwrap();
+        assert_eq!(date["start"], "2022-09-05T18:00:00");
+        assert_eq!(date["time_zone"], "Europe/Bucharest");
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     database::DatabaseKind,
-    models::{Page, Parent, PropertyValue},
+    models::{Block, File, Icon, Page, Parent, PropertyValue},
     Client,
 };
 use reqwest::Method;
@@ -11,6 +11,22 @@ use std::{collections::HashMap, error::Error};
 struct CreatePageProps {
     parent: Parent,
     properties: HashMap<String, PropertyValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Block>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cover: Option<File>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<Icon>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdatePageProps {
+    properties: HashMap<String, PropertyValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivePageProps {
+    archived: bool,
 }
 
 impl<'a> Client<'a> {
@@ -18,20 +34,21 @@ impl<'a> Client<'a> {
         &self,
         kind: DatabaseKind,
         properties: HashMap<String, PropertyValue>,
+        children: Vec<Block>,
+        cover: Option<File>,
+        icon: Option<Icon>,
     ) -> Result<Page, Box<dyn Error>> {
-        let db_id = match kind {
-            DatabaseKind::Source => &self.config.notion_source_database_id,
-            DatabaseKind::Feed => &self.config.notion_feed_database_id,
-        };
-
         let path = "/pages";
 
         let create_page_props = CreatePageProps {
             parent: Parent {
                 parent_type: "database_id".to_string(),
-                database_id: Some(db_id.to_string()),
+                database_id: Some(kind.id(self.config).to_string()),
             },
             properties,
+            children,
+            cover,
+            icon,
         };
 
         let res = self
@@ -43,6 +60,37 @@ impl<'a> Client<'a> {
 
         Ok(res.json::<Page>().await?)
     }
+
+    pub async fn update_page(
+        &self,
+        page_id: &str,
+        properties: HashMap<String, PropertyValue>,
+    ) -> Result<Page, Box<dyn Error>> {
+        let path = format!("/pages/{}", page_id);
+
+        let res = self
+            .build_request(Method::PATCH, path)
+            .json(&UpdatePageProps { properties })
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json::<Page>().await?)
+    }
+
+    /// Moves a page to the trash.
+    pub async fn archive_page(&self, page_id: &str) -> Result<Page, Box<dyn Error>> {
+        let path = format!("/pages/{}", page_id);
+
+        let res = self
+            .build_request(Method::PATCH, path)
+            .json(&ArchivePageProps { archived: true })
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json::<Page>().await?)
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +116,7 @@ mod tests {
                             content: String::from("Test Link"),
                             link: None,
                         },
+                        annotations: None,
                     }],
                 },
             ),
@@ -83,7 +132,9 @@ mod tests {
             ),
         ]);
 
-        let page = client.create_page(DatabaseKind::Source, page_props).await;
+        let page = client
+            .create_page(DatabaseKind::Source, page_props, vec![], None, None)
+            .await;
 
         assert!(page.is_ok());
     }
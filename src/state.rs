@@ -0,0 +1,120 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, error::Error, fs, io::ErrorKind, path::PathBuf};
+
+/// Data remembered between runs, stored as JSON in the state file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// State of each source, by source link
+    #[serde(default)]
+    sources: HashMap<String, SourceState>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SourceState {
+    /// Publication date of the newest item already ingested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_item_date: Option<DateTime<FixedOffset>>,
+    /// Text of the page of `Watch` sources, compared on the next run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+    /// Message-IDs of the newsletters already processed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub message_ids: Vec<String>,
+    /// Favicon of the source website, looked up once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Snapshot {
+    /// SHA-256 of the text, in hexadecimal
+    pub hash: String,
+    pub text: String,
+}
+
+impl Snapshot {
+    pub fn new(text: String) -> Snapshot {
+        Self {
+            hash: format!("{:x}", Sha256::digest(text.as_bytes())),
+            text,
+        }
+    }
+}
+
+impl State {
+    /// Loads the state file. Without a path, the state is kept in memory only.
+    pub fn load(path: Option<&str>) -> Result<State, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => return Ok(State::default()),
+        };
+
+        let mut state = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<State>(&content)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => State::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        state.path = Some(path);
+
+        Ok(state)
+    }
+
+    pub fn source(&self, link: &str) -> Option<&SourceState> {
+        self.sources.get(link)
+    }
+
+    pub fn source_mut(&mut self, link: &str) -> &mut SourceState {
+        self.sources.entry(link.to_string()).or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn it_saves_and_loads_state() {
+        let path = env::temp_dir().join(format!("notion-feed-state-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let date = DateTime::parse_from_rfc3339("2022-09-05T10:00:00+02:00").unwrap();
+
+        let mut state = State::load(Some(path)).unwrap();
+        assert!(state.source("https://bereghici.dev/rss").is_none());
+
+        state.source_mut("https://bereghici.dev/rss").last_item_date = Some(date);
+        state.save().unwrap();
+
+        let state = State::load(Some(path)).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            state
+                .source("https://bereghici.dev/rss")
+                .unwrap()
+                .last_item_date,
+            Some(date)
+        );
+    }
+
+    #[test]
+    fn it_keeps_state_in_memory_without_path() {
+        let mut state = State::load(None).unwrap();
+        state.source_mut("https://bereghici.dev/rss");
+
+        assert!(state.save().is_ok());
+    }
+}